@@ -0,0 +1,69 @@
+pub const MAX_BRUSH_SIZE: u8 = 9;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BrushShape {
+    Square,
+    Round,
+    Diamond,
+}
+
+impl BrushShape {
+    pub fn next(self) -> Self {
+        match self {
+            BrushShape::Square => BrushShape::Round,
+            BrushShape::Round => BrushShape::Diamond,
+            BrushShape::Diamond => BrushShape::Square,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Brush {
+    pub size: u8,
+    pub shape: BrushShape,
+}
+
+impl Brush {
+    pub fn new() -> Self {
+        Self {
+            size: 1,
+            shape: BrushShape::Square,
+        }
+    }
+
+    pub fn bigger(self) -> Self {
+        Self {
+            size: (self.size + 1).min(MAX_BRUSH_SIZE),
+            ..self
+        }
+    }
+
+    pub fn smaller(self) -> Self {
+        Self {
+            size: (self.size - 1).max(1),
+            ..self
+        }
+    }
+
+    ///positions covered when the brush is centered at `center`, they can be out of the image
+    pub fn footprint(&self, center: (i32, i32)) -> Vec<(i32, i32)> {
+        let size = self.size as i32;
+        let start = -(size - 1) / 2;
+        let mut positions = vec![];
+        for i in 0..size {
+            for j in 0..size {
+                //distances from the center of the brush, doubled so even sizes stay integers
+                let (dy, dx) = (2 * i - (size - 1), 2 * j - (size - 1));
+                let covered = match self.shape {
+                    BrushShape::Square => true,
+                    BrushShape::Round => dy * dy + dx * dx <= size * size - size,
+                    BrushShape::Diamond => dy.abs() + dx.abs() <= size,
+                };
+                if covered {
+                    positions.push((center.0 + start + i, center.1 + start + j));
+                }
+            }
+        }
+        positions
+    }
+}
@@ -0,0 +1,318 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use libtif::image::TifImage;
+use libtif::pixel::PixelColor;
+
+use crate::area::Point;
+use crate::export;
+use crate::fill;
+use crate::pallete::parse_color_name;
+use crate::recolor;
+use crate::shapes;
+
+///tallest image `resize` makes, the width is at most 255 anyway
+pub const MAX_HEIGHT: u64 = 10_000;
+
+///editor commands, run by `--script` and by the `:` prompt of the editor.
+///positions are written as `y x`, areas as the two opposite corners `y1 x1 y2 x2`
+#[derive(Clone, Debug)]
+pub enum Command {
+    ///`pixel y x color`
+    Pixel { pos: Point, color: PixelColor },
+    ///`fill y1 x1 y2 x2 color`
+    Fill { from: Point, to: Point, color: PixelColor },
+    ///`line y1 x1 y2 x2 color`
+    Line { from: Point, to: Point, color: PixelColor },
+    ///`rect y1 x1 y2 x2 color`, only the outline
+    Rect { from: Point, to: Point, color: PixelColor },
+    ///`flood y x color`
+    Flood { pos: Point, color: PixelColor },
+    ///`replace from_color to_color`
+    Replace { from: PixelColor, to: PixelColor },
+    ///`resize height width [color]`, new pixels get `color` (black by default)
+    Resize { height: u64, width: u8, color: PixelColor },
+    ///`save [path]`, saves to the file being edited when no path is given
+    Save { path: Option<String> },
+}
+
+impl Command {
+    ///`None` for empty lines and comments (lines starting with `#`)
+    pub fn parse(line: &str) -> Result<Option<Self>> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(None);
+        }
+        let mut args = line.split_whitespace();
+        let name = args.next().unwrap_or_default();
+        let args: Vec<&str> = args.collect();
+        let command = match (name, args.as_slice()) {
+            ("pixel", [y, x, color]) => Command::Pixel {
+                pos: point(y, x)?,
+                color: color_arg(color)?,
+            },
+            ("fill", [y1, x1, y2, x2, color]) => Command::Fill {
+                from: point(y1, x1)?,
+                to: point(y2, x2)?,
+                color: color_arg(color)?,
+            },
+            ("line", [y1, x1, y2, x2, color]) => Command::Line {
+                from: point(y1, x1)?,
+                to: point(y2, x2)?,
+                color: color_arg(color)?,
+            },
+            ("rect", [y1, x1, y2, x2, color]) => Command::Rect {
+                from: point(y1, x1)?,
+                to: point(y2, x2)?,
+                color: color_arg(color)?,
+            },
+            ("flood", [y, x, color]) => Command::Flood {
+                pos: point(y, x)?,
+                color: color_arg(color)?,
+            },
+            ("replace", [from, to]) => Command::Replace {
+                from: color_arg(from)?,
+                to: color_arg(to)?,
+            },
+            ("resize", [height, width, rest @ ..]) if rest.len() <= 1 => Command::Resize {
+                height: height
+                    .parse()
+                    .ok()
+                    .filter(|height| (1..=MAX_HEIGHT).contains(height))
+                    .ok_or_else(|| {
+                        anyhow!("the height must be between 1 and {}, found {:?}", MAX_HEIGHT, height)
+                    })?,
+                width: width
+                    .parse()
+                    .ok()
+                    .filter(|width| *width > 0)
+                    .ok_or_else(|| anyhow!("the width must be between 1 and 255, found {:?}", width))?,
+                color: match rest.first() {
+                    Some(color) => color_arg(color)?,
+                    None => PixelColor::Black,
+                },
+            },
+            ("save", []) => Command::Save { path: None },
+            ("save", [path]) => Command::Save {
+                path: Some(path.to_string()),
+            },
+            ("pixel" | "fill" | "line" | "rect" | "flood" | "replace" | "resize" | "save", _) => {
+                return Err(anyhow!("wrong arguments for {}: {}", name, usage(name)))
+            }
+            _ => return Err(anyhow!("unknown command {:?}", name)),
+        };
+        Ok(Some(command))
+    }
+
+    ///`file` is the file being edited, used by `save` without a path
+    pub fn run(&self, tif_image: &mut TifImage, file: &str) -> Result<()> {
+        match self {
+            Command::Pixel { pos, color } => set_points(tif_image, &[*pos], *color),
+            //both corners are checked first, so huge areas fail before their points are built
+            Command::Fill { from, to, color } => {
+                check_bounds(tif_image, *from)?;
+                check_bounds(tif_image, *to)?;
+                let mut points = vec![];
+                for y in from.y.min(to.y)..=from.y.max(to.y) {
+                    for x in from.x.min(to.x)..=from.x.max(to.x) {
+                        points.push(Point::new(y, x));
+                    }
+                }
+                set_points(tif_image, &points, *color)
+            }
+            Command::Line { from, to, color } => {
+                check_bounds(tif_image, *from)?;
+                check_bounds(tif_image, *to)?;
+                set_points(tif_image, &shapes::line(*from, *to), *color)
+            }
+            Command::Rect { from, to, color } => {
+                check_bounds(tif_image, *from)?;
+                check_bounds(tif_image, *to)?;
+                set_points(tif_image, &shapes::rectangle(*from, *to), *color)
+            }
+            Command::Flood { pos, color } => {
+                check_bounds(tif_image, *pos)?;
+                for (y, x) in fill::flood(tif_image, (pos.y as usize, pos.x as usize)) {
+                    tif_image.pixels[y][x] = *color;
+                }
+                Ok(())
+            }
+            Command::Replace { from, to } => {
+                for (y, x) in recolor::matching(tif_image, *from) {
+                    tif_image.pixels[y][x] = *to;
+                }
+                Ok(())
+            }
+            Command::Resize {
+                height,
+                width,
+                color,
+            } => {
+                resize(tif_image, *height, *width, *color);
+                Ok(())
+            }
+            Command::Save { path } => {
+                export::save_tif(tif_image, path.as_deref().unwrap_or(file))
+            }
+        }
+    }
+}
+
+///runs every line of `script`, errors tell the line that failed
+pub fn run_script(script: &str, tif_image: &mut TifImage, file: &str) -> Result<()> {
+    for (n, line) in script.lines().enumerate() {
+        run_line(line, tif_image, file)
+            .with_context(|| format!("line {}: {}", n + 1, line.trim()))?;
+    }
+    Ok(())
+}
+
+///parses and runs a single line, empty lines and comments do nothing
+pub fn run_line(line: &str, tif_image: &mut TifImage, file: &str) -> Result<()> {
+    if let Some(command) = Command::parse(line)? {
+        command.run(tif_image, file)?;
+    }
+    Ok(())
+}
+
+///keeps the top left part of the image, new pixels get `color`
+pub fn resize(tif_image: &mut TifImage, height: u64, width: u8, color: PixelColor) {
+    tif_image
+        .pixels
+        .resize(height as usize, vec![color; width as usize]);
+    for row in tif_image.pixels.iter_mut() {
+        row.resize(width as usize, color);
+    }
+    tif_image.height = height;
+    tif_image.width = width;
+}
+
+fn usage(name: &str) -> &'static str {
+    match name {
+        "pixel" => "pixel y x color",
+        "fill" => "fill y1 x1 y2 x2 color",
+        "line" => "line y1 x1 y2 x2 color",
+        "rect" => "rect y1 x1 y2 x2 color",
+        "flood" => "flood y x color",
+        "replace" => "replace from_color to_color",
+        "resize" => "resize height width [color]",
+        _ => "save [path]",
+    }
+}
+
+fn point(y: &str, x: &str) -> Result<Point> {
+    let coord = |s: &str| {
+        s.parse::<i32>()
+            .with_context(|| format!("invalid coordinate {:?}", s))
+    };
+    Ok(Point::new(coord(y)?, coord(x)?))
+}
+
+fn color_arg(name: &str) -> Result<PixelColor> {
+    parse_color_name(name).ok_or_else(|| anyhow!("unknown color {:?}", name))
+}
+
+fn check_bounds(tif_image: &TifImage, point: Point) -> Result<()> {
+    let inside = point.y >= 0
+        && point.x >= 0
+        && (point.y as u64) < tif_image.height
+        && point.x < tif_image.width as i32;
+    if !inside {
+        return Err(anyhow!(
+            "({}, {}) is out of the {}x{} image",
+            point.y,
+            point.x,
+            tif_image.height,
+            tif_image.width
+        ));
+    }
+    Ok(())
+}
+
+fn set_points(tif_image: &mut TifImage, points: &[Point], color: PixelColor) -> Result<()> {
+    for point in points {
+        check_bounds(tif_image, *point)?;
+    }
+    for point in points {
+        tif_image.pixels[point.y as usize][point.x as usize] = color;
+    }
+    Ok(())
+}
+
+///an image where every pixel is black, for the tests of every module
+#[cfg(test)]
+pub fn black(height: u64, width: u8) -> TifImage {
+    TifImage {
+        height,
+        width,
+        pixels: vec![vec![PixelColor::Black; width as usize]; height as usize],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comments_and_empty_lines() {
+        assert!(Command::parse("").unwrap().is_none());
+        assert!(Command::parse("   # fill 0 0 1 1 red").unwrap().is_none());
+    }
+
+    #[test]
+    fn invalid_commands() {
+        assert!(Command::parse("paint 0 0 red").is_err());
+        assert!(Command::parse("fill 0 0 1 red").is_err());
+        assert!(Command::parse("pixel 0 x red").is_err());
+        assert!(Command::parse("pixel 0 0 pink").is_err());
+        assert!(Command::parse("resize 0 4").is_err());
+        assert!(Command::parse("resize 4 256").is_err());
+        assert!(Command::parse("resize 4000000000 255").is_err());
+    }
+
+    #[test]
+    fn huge_areas_fail_right_away() {
+        let mut tif_image = black(3, 3);
+        for command in ["fill", "line", "rect"] {
+            let line = format!("{} 0 0 2000000000 2000000000 red", command);
+            assert!(run_line(&line, &mut tif_image, "unused.tif").is_err());
+        }
+        assert_eq!(tif_image.pixels, black(3, 3).pixels);
+    }
+
+    #[test]
+    fn script() {
+        let mut tif_image = black(3, 3);
+        let script = "fill 0 0 2 2 white\n# a comment\n\nline 0 0 2 2 red\nreplace white blue\n";
+        run_script(script, &mut tif_image, "unused.tif").unwrap();
+        use PixelColor::{Blue, Red};
+        assert_eq!(
+            tif_image.pixels,
+            vec![vec![Red, Blue, Blue], vec![Blue, Red, Blue], vec![Blue, Blue, Red]]
+        );
+    }
+
+    #[test]
+    fn errors_tell_the_line() {
+        let mut tif_image = black(3, 3);
+        let error = run_script("pixel 0 0 red\npixel 5 5 red", &mut tif_image, "unused.tif")
+            .unwrap_err();
+        assert!(format!("{:#}", error).starts_with("line 2: pixel 5 5 red"));
+        //out of bounds commands dont paint anything
+        let error = run_script("line 0 0 0 5 red", &mut tif_image, "unused.tif");
+        assert!(error.is_err());
+        assert_eq!(tif_image.pixels[0][1], PixelColor::Black);
+    }
+
+    #[test]
+    fn resize_keeps_the_top_left() {
+        let mut tif_image = black(2, 2);
+        run_script("resize 3 1 red\nresize 3 2 green", &mut tif_image, "unused.tif").unwrap();
+        use PixelColor::{Black, Green, Red};
+        assert_eq!((tif_image.height, tif_image.width), (3, 2));
+        assert_eq!(
+            tif_image.pixels,
+            vec![vec![Black, Green], vec![Black, Green], vec![Red, Green]]
+        );
+    }
+}
@@ -1,15 +1,24 @@
-use pancurses::{Window, COLOR_PAIR};
+use pancurses::COLOR_PAIR;
+
+use crate::screen::Screen;
+
+use crate::brush::Brush;
 
 pub struct Cursor {
     pub pos: (i32, i32),
+    pub brush: Brush,
     hidden: bool,
+    ///the off half of a blink
+    pub blinked: bool,
 }
 
 impl Cursor {
     pub fn new() -> Self {
         Self {
             pos: (0, 0),
+            brush: Brush::new(),
             hidden: false,
+            blinked: false,
         }
     }
 
@@ -20,10 +29,23 @@ impl Cursor {
     pub fn coord_as_usize(&self) -> (usize, usize) {
         (self.pos.0 as usize, self.pos.1 as usize)
     }
-    pub fn draw(&self, w: &Window) {
-        if !self.hidden {
+    ///the pixels covered by the brush, clipped to the image
+    pub fn footprint(&self, image: (u64, u8)) -> Vec<(usize, usize)> {
+        self.brush
+            .footprint(self.pos)
+            .into_iter()
+            .filter(|pos| pos.0 >= 0 && pos.0 < image.0 as i32 && pos.1 >= 0 && pos.1 < image.1 as i32)
+            .map(|pos| (pos.0 as usize, pos.1 as usize))
+            .collect()
+    }
+    ///`origin` is the screen position of the top left pixel of the image
+    pub fn draw(&self, w: &Screen, image: (u64, u8), origin: (i32, i32)) {
+        if !self.hidden && !self.blinked {
             w.attrset(COLOR_PAIR(9));
-            w.mvaddch(self.pos.0, self.pos.1, '#');
+            for pos in self.footprint(image) {
+                let c = if pos == self.coord_as_usize() { '#' } else { '+' };
+                w.mvaddch(origin.0 + pos.0 as i32, origin.1 + pos.1 as i32, c);
+            }
             w.attroff(COLOR_PAIR(9));
         }
     }
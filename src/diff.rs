@@ -0,0 +1,228 @@
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use libtif::image::TifImage;
+use libtif::pixel::PixelColor;
+
+use crate::pallete::Pallete;
+
+///((top, left), (bottom, right)), both corners included
+pub type Bounds = ((usize, usize), (usize, usize));
+
+///how the `diff` subcommand draws the two images
+#[derive(Clone, Copy, PartialEq, Eq, Debug, clap::ValueEnum)]
+pub enum View {
+    ///the old image on the left, the new one on the right
+    SideBySide,
+    ///only the new image, with the changed pixels marked
+    Overlay,
+    ///only the numbers, without drawing the images
+    None,
+}
+
+fn pixel(tif_image: &TifImage, (y, x): (usize, usize)) -> Option<PixelColor> {
+    tif_image.pixels.get(y).and_then(|row| row.get(x)).copied()
+}
+
+///a pixel that only exists in one of the images counts as changed
+pub fn is_changed(old: &TifImage, new: &TifImage, pos: (usize, usize)) -> bool {
+    pixel(old, pos) != pixel(new, pos)
+}
+
+///every changed pixel, row by row, over the size of the biggest image
+pub fn changed_pixels(old: &TifImage, new: &TifImage) -> Vec<(usize, usize)> {
+    let height = old.height.max(new.height) as usize;
+    let width = old.width.max(new.width) as usize;
+    let mut changed = vec![];
+    for y in 0..height {
+        for x in 0..width {
+            if is_changed(old, new, (y, x)) {
+                changed.push((y, x));
+            }
+        }
+    }
+    changed
+}
+
+///bounds of every group of touching changed pixels (diagonals included)
+pub fn regions(changed: &[(usize, usize)]) -> Vec<Bounds> {
+    let mut left: HashSet<(usize, usize)> = changed.iter().copied().collect();
+    let mut regions = vec![];
+    for start in changed {
+        if !left.remove(start) {
+            continue;
+        }
+        let mut bounds = (*start, *start);
+        let mut stack = vec![*start];
+        while let Some((y, x)) = stack.pop() {
+            bounds.0 = (bounds.0 .0.min(y), bounds.0 .1.min(x));
+            bounds.1 = (bounds.1 .0.max(y), bounds.1 .1.max(x));
+            for ny in y.saturating_sub(1)..=y + 1 {
+                for nx in x.saturating_sub(1)..=x + 1 {
+                    if left.remove(&(ny, nx)) {
+                        stack.push((ny, nx));
+                    }
+                }
+            }
+        }
+        regions.push(bounds);
+    }
+    regions.sort();
+    regions
+}
+
+///the numbers printed by the `diff` subcommand
+pub fn report(old: &TifImage, new: &TifImage) -> String {
+    let changed = changed_pixels(old, new);
+    let mut out = String::new();
+    if (old.height, old.width) != (new.height, new.width) {
+        writeln!(
+            out,
+            "size: {}x{} -> {}x{}",
+            old.height, old.width, new.height, new.width
+        )
+        .ok();
+    }
+    writeln!(out, "changed pixels: {}", changed.len()).ok();
+    if let Some(bounds) = bounds_of(&changed) {
+        writeln!(out, "changed area: {}", bounds_to_string(bounds)).ok();
+    }
+    for bounds in regions(&changed) {
+        writeln!(out, "  region {}", bounds_to_string(bounds)).ok();
+    }
+    out
+}
+
+fn bounds_of(changed: &[(usize, usize)]) -> Option<Bounds> {
+    let first = *changed.first()?;
+    Some(changed.iter().fold((first, first), |(top_left, bottom_right), (y, x)| {
+        (
+            (top_left.0.min(*y), top_left.1.min(*x)),
+            (bottom_right.0.max(*y), bottom_right.1.max(*x)),
+        )
+    }))
+}
+
+fn bounds_to_string(((top, left), (bottom, right)): Bounds) -> String {
+    format!(
+        "({}, {}) to ({}, {}), {}x{}",
+        top,
+        left,
+        bottom,
+        right,
+        bottom - top + 1,
+        right - left + 1
+    )
+}
+
+///two columns of true color escape codes, changed pixels are marked with `<>`
+fn cell(pallete: &Pallete, color: Option<PixelColor>, changed: bool) -> String {
+    let Some(color) = color else {
+        //outside of the image
+        return String::from("\x1b[0m  ");
+    };
+    let bg = pallete.rgb_of(&color);
+    let mut out = format!("\x1b[48;2;{};{};{}m", bg.0, bg.1, bg.2);
+    if changed {
+        let fg = if pallete.is_light(&color) { 30 } else { 97 };
+        out.push_str(&format!("\x1b[{}m<>", fg));
+    } else {
+        out.push_str("  ");
+    }
+    out
+}
+
+fn row(pallete: &Pallete, tif_image: &TifImage, other: &TifImage, y: usize, width: usize) -> String {
+    (0..width)
+        .map(|x| cell(pallete, pixel(tif_image, (y, x)), is_changed(tif_image, other, (y, x))))
+        .collect()
+}
+
+pub fn render(old: &TifImage, new: &TifImage, pallete: &Pallete, view: View) -> String {
+    let height = old.height.max(new.height) as usize;
+    let width = old.width.max(new.width) as usize;
+    let mut out = String::new();
+    for y in 0..height {
+        match view {
+            View::SideBySide => {
+                out.push_str(&row(pallete, old, new, y, width));
+                out.push_str("\x1b[0m    ");
+                out.push_str(&row(pallete, new, old, y, width));
+            }
+            View::Overlay => {
+                out.extend((0..width).map(|x| {
+                    //removed pixels are shown with their old color
+                    let color = pixel(new, (y, x)).or_else(|| pixel(old, (y, x)));
+                    cell(pallete, color, is_changed(old, new, (y, x)))
+                }));
+            }
+            View::None => return out,
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+///the saved version of the file being edited, keeps count of the pixels changed since
+pub struct Comparison {
+    pub saved: TifImage,
+    pub changed: usize,
+}
+
+impl Comparison {
+    pub fn new(saved: TifImage, current: &TifImage) -> Self {
+        let changed = changed_pixels(&saved, current).len();
+        Self { saved, changed }
+    }
+
+    pub fn is_changed(&self, current: &TifImage, pos: (usize, usize)) -> bool {
+        is_changed(&self.saved, current, pos)
+    }
+
+    ///called before the pixel at `pos` of `current` becomes `color`
+    pub fn update(&mut self, current: &TifImage, pos: (usize, usize), color: PixelColor) {
+        let was_changed = self.is_changed(current, pos);
+        let will_change = pixel(&self.saved, pos) != Some(color);
+        match (was_changed, will_change) {
+            (false, true) => self.changed += 1,
+            (true, false) => self.changed -= 1,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regions_of_changed_pixels() {
+        let old = TifImage {
+            height: 4,
+            width: 5,
+            pixels: vec![vec![PixelColor::Black; 5]; 4],
+        };
+        let mut new = old.clone();
+        new.pixels[0][0] = PixelColor::Red;
+        new.pixels[1][1] = PixelColor::Red;
+        new.pixels[3][4] = PixelColor::Blue;
+        let changed = changed_pixels(&old, &new);
+        assert_eq!(changed.len(), 3);
+        assert_eq!(regions(&changed), vec![((0, 0), (1, 1)), ((3, 4), (3, 4))]);
+    }
+
+    #[test]
+    fn pixels_outside_of_the_other_image_changed() {
+        let old = TifImage {
+            height: 1,
+            width: 2,
+            pixels: vec![vec![PixelColor::Black; 2]],
+        };
+        let new = TifImage {
+            height: 2,
+            width: 2,
+            pixels: vec![vec![PixelColor::Black; 2]; 2],
+        };
+        assert_eq!(changed_pixels(&old, &new), vec![(1, 0), (1, 1)]);
+    }
+}
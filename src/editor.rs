@@ -1,39 +1,140 @@
-use super::color::Color;
 use super::pallete::Pallete;
 use crate::area::Area;
 use crate::area::Point;
+use crate::brush::Brush;
 use crate::color;
+use crate::command;
 use crate::cursor::Cursor;
+use crate::diff::Comparison;
+use crate::export;
+use crate::fill;
+use crate::font::Font;
+use crate::grid;
+use crate::grid::Grid;
+use crate::layout::Layout;
+use crate::layout::Rect;
 use crate::mode::Mode;
+use crate::pattern::Pattern;
+use crate::pattern::PATTERNS;
+use crate::recolor;
+use crate::recolor::Remap;
+use crate::screen::Screen;
+use crate::selection;
+use crate::selection::Combine;
+use crate::selection::Selection;
+use crate::selection::SelectionTool;
+use crate::shapes;
+use crate::stamp::Library;
+use crate::stamp::Stamp;
+use crate::stats::Stats;
+use crate::symmetry::Symmetry;
 use anyhow::anyhow;
 use anyhow::Context;
 use anyhow::Result;
 use libtif::image::TifImage;
 use libtif::pixel::PixelColor;
 use pancurses::COLOR_PAIR;
-use pancurses::{initscr, Window};
+use pancurses::initscr;
+use std::collections::HashSet;
 use std::ops::Deref;
 
 pub struct Editor {
-    pub window: Window,
+    pub screen: Screen,
     pub tif_image: TifImage,
     pub mode: Mode,
     pub selected_color: PixelColor,
+    pub secondary_color: PixelColor,
     pub cursor: Cursor,
     pub pallete: Pallete,
     pub area: Option<Area>,
+    ///the selected pixels, the outline around them is drawn over the canvas
+    pub selection: Option<Selection>,
+    ///the selection from before entering Area mode, the selection tool changes a copy of it
+    selection_base: Option<Selection>,
+    ///path of the cursor since the lasso was anchored
+    lasso: Vec<(i32, i32)>,
+    pub selection_tool: SelectionTool,
+    pub combine: Combine,
+    ///moves the dashes of the outline, so it looks like it is marching
+    outline_phase: usize,
+    ///the pixels copied from the selection
+    pub clipboard: Option<Stamp>,
+    ///what follows the cursor in Paste mode
+    pub paste: Option<Stamp>,
+    ///the stamps browsed in Paste mode, `None` when pasting the clipboard
+    pub library: Option<Library>,
+    ///directory of the stamp library
+    pub stamps_dir: String,
+    ///the text being typed in Text mode, previewed like a stamp
+    pub text: String,
+    pub font: Font,
+    pub symmetry: Symmetry,
+    pub pattern: Pattern,
+    ///the palette swap being edited in Remap mode, previewed on the canvas
+    pub remap: Option<Remap>,
+    pub grid: Grid,
+    pub show_rulers: bool,
+    ///tile mode: the image is repeated around the canvas and the cursor and the brush wrap around its edges
+    pub tile: bool,
+    ///the file being edited, `save` writes to it
+    pub file: String,
+    ///the command being typed in Command mode
+    pub command_line: String,
+    ///set while the pixels changed since the last save are marked on the canvas
+    pub comparison: Option<Comparison>,
+    pub stats: Stats,
+    ///the statistics are shown instead of the help
+    pub show_stats: bool,
+    ///the terminal cant fit the editor, only a warning is drawn until it is resized
+    pub too_small: bool,
+    ///pixels that changed since the last `flush`, drawn again all at once
+    dirty: HashSet<(usize, usize)>,
+    ///the statistics panel is out of date
+    dirty_stats: bool,
 }
 
 impl Editor {
-    pub fn new(tif_image: TifImage) -> Self {
+    pub fn new(tif_image: TifImage, pallete: Pallete, file: String) -> Self {
+        Self::with_screen(Screen::Terminal(initscr()), tif_image, pallete, file)
+    }
+
+    pub fn with_screen(screen: Screen, tif_image: TifImage, pallete: Pallete, file: String) -> Self {
+        let stats = Stats::new(&tif_image, &pallete, PixelColor::White);
         Self {
-            window: initscr(),
+            screen,
             tif_image,
             mode: Mode::Selection,
             selected_color: PixelColor::Black,
+            secondary_color: PixelColor::White,
             cursor: Cursor::new(),
-            pallete: Pallete::new(),
+            pallete,
             area: None,
+            selection: None,
+            selection_base: None,
+            lasso: vec![],
+            selection_tool: SelectionTool::Rectangle,
+            combine: Combine::Replace,
+            outline_phase: 0,
+            clipboard: None,
+            paste: None,
+            library: None,
+            stamps_dir: String::from("stamps"),
+            text: String::new(),
+            font: Font::Small,
+            symmetry: Symmetry::None,
+            pattern: Pattern::Solid,
+            remap: None,
+            grid: Grid::new(),
+            show_rulers: false,
+            tile: false,
+            file,
+            command_line: String::new(),
+            comparison: None,
+            stats,
+            show_stats: false,
+            too_small: false,
+            dirty: HashSet::new(),
+            dirty_stats: false,
         }
     }
 
@@ -50,28 +151,203 @@ impl Editor {
     }
     pub fn set_mode(&mut self, m: Mode) {
         if self.mode == Mode::Area && m != Mode::Area {
-            self.draw_area_pixels().ok();
+            //the selection is kept
+            self.selection_base = None;
+            self.lasso.clear();
+        }
+        if self.mode.shape().is_some() && m.shape().is_none() {
+            self.clear_shape_preview();
+            self.cursor.toogle_hidden();
+        }
+        if self.mode == Mode::Remap && m != Mode::Remap {
+            self.remap = None;
+            self.draw_remap();
+            self.draw_image();
+            self.draw_cursor();
+        }
+        if self.mode == Mode::Command && m != Mode::Command {
+            self.command_line.clear();
+            self.draw_command_line();
+        }
+        if matches!(self.mode, Mode::Paste | Mode::Text) && m != self.mode {
+            self.mark_paste_dirty();
+            self.paste = None;
+            self.library = None;
+            self.text.clear();
             self.cursor.toogle_hidden();
         }
+        if self.mode == Mode::Text && m != Mode::Text {
+            self.draw_message("");
+        }
         self.mode = m;
         self.draw_status();
         self.draw_help().ok(); //we dont really need to handle that error
-        self.refresh();
     }
     pub fn get_image_size(&self) -> (u64, u8) {
         (self.tif_image.height, self.tif_image.width)
     }
     pub fn get_window_size(&self) -> (i32, i32) {
-        self.window.get_max_yx()
+        self.screen.get_max_yx()
     }
     pub fn set_selected_color(&mut self, color: PixelColor) {
         self.selected_color = color;
+        if self.mode == Mode::Text {
+            self.update_text();
+        }
+        self.draw_color_pallete();
+        self.draw_pattern_list();
+        self.draw_status();
+    }
+
+    pub fn set_symmetry(&mut self, symmetry: Symmetry) {
+        self.symmetry = symmetry;
+        self.draw_border();
+        self.draw_status();
+    }
+
+    pub fn set_pattern(&mut self, pattern: Pattern) {
+        self.pattern = pattern;
+        self.draw_pattern_list();
+        self.draw_status();
+    }
+
+    ///the color `pos` gets when it's painted with `color` using the current pattern,
+    ///the pattern mixes the primary and secondary colors
+    fn patterned(&self, pos: (usize, usize), color: PixelColor) -> PixelColor {
+        let other = if color == self.secondary_color {
+            self.selected_color
+        } else {
+            self.secondary_color
+        };
+        self.pattern.color_at(pos, color, other)
+    }
+
+    ///replace every pixel that has the color under the cursor by `color`,
+    ///only the selected ones when there is a selection
+    pub fn replace_color_at_cursor(&mut self, color: PixelColor) -> Result<()> {
+        //nothing to replace in images without pixels
+        let Some(from) = self.get_pix(&self.cursor.coord_as_usize()).copied() else {
+            return Ok(());
+        };
+        for pos in recolor::matching(&self.tif_image, from) {
+            if self.is_selected(pos) {
+                self.set_pix_color(pos, color)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn remap_mode(&mut self) {
+        self.remap = Some(Remap::identity(&self.pallete));
+        self.set_mode(Mode::Remap);
+        self.draw_remap();
+    }
+
+    ///set the color that replaces the current slot of the palette swap
+    pub fn set_remap_target(&mut self, color: PixelColor) {
+        if let Some(remap) = self.remap.as_mut() {
+            remap.set_target(color);
+        }
+        self.draw_remap();
+        self.draw_image();
+    }
+
+    pub fn move_remap_slot(&mut self, offset: i32) {
+        let slots = self.pallete.colors.len() as i32;
+        if let Some(remap) = self.remap.as_mut() {
+            remap.slot = (remap.slot as i32 + offset).rem_euclid(slots) as usize;
+        }
+        self.draw_remap();
+    }
+
+    pub fn apply_remap(&mut self) {
+        if let Some(remap) = self.remap.take() {
+            let before = self.tif_image.clone();
+            remap.apply(&mut self.tif_image, &self.pallete);
+            self.keep_unselected(&before);
+            self.image_changed();
+        }
+        self.set_mode(Mode::Selection);
+    }
+
+    ///below the palette: the color that replaces every slot and `^^` under the slot being edited
+    fn draw_remap(&self) {
+        let y_pos = self.pallete_y_pos() + 2;
+        for (slot, color) in self.pallete.colors.iter().enumerate() {
+            let pos = slot as i32 * 2;
+            match &self.remap {
+                Some(remap) => {
+                    let target = remap.target_of(&self.pallete, color);
+                    self.attrset(COLOR_PAIR(self.pallete.pair_of(&target)));
+                    self.mvprintw(y_pos, pos, "  ");
+                    self.attroff(COLOR_PAIR(self.pallete.pair_of(&target)));
+                    let marker = if remap.slot == slot { "^^" } else { "  " };
+                    self.mvprintw(y_pos + 1, pos, marker);
+                }
+                None => {
+                    self.mvprintw(y_pos, pos, "  ");
+                    self.mvprintw(y_pos + 1, pos, "  ");
+                }
+            }
+        }
+    }
+
+    ///bucket fill the region of the pixel under the cursor, it doesnt leave the selection
+    pub fn bucket_fill(&mut self, color: PixelColor) -> Result<()> {
+        let region = fill::flood_within(&self.tif_image, self.cursor.coord_as_usize(), |pos| {
+            self.is_selected(pos)
+        });
+        for pos in region {
+            self.paint_pix(pos, self.patterned(pos, color))?;
+        }
+        Ok(())
+    }
+
+    pub fn set_secondary_color(&mut self, color: PixelColor) {
+        self.secondary_color = color;
+        self.image_changed();
+        self.draw_color_pallete();
+        self.draw_pattern_list();
+        self.draw_status();
+    }
+
+    pub fn swap_colors(&mut self) {
+        std::mem::swap(&mut self.selected_color, &mut self.secondary_color);
+        self.image_changed();
+        self.draw_color_pallete();
+        self.draw_pattern_list();
         self.draw_status();
-        self.refresh();
+    }
+
+    ///eyedropper: select the color of the pixel under the cursor
+    pub fn pick_color_at_cursor(&mut self) {
+        if let Some(color) = self.get_pix(&self.cursor.coord_as_usize()).copied() {
+            self.set_selected_color(color);
+        }
+    }
+
+    ///move the cursor to `pos` and pick the color there
+    pub fn pick_color_at(&mut self, pos: (i32, i32)) -> Result<()> {
+        self.set_cursor_pos(pos)?;
+        self.pick_color_at_cursor();
+        Ok(())
     }
 
     pub fn area_mode(&mut self) {
         self.mode = Mode::Area;
+        self.selection_base = self.selection.clone();
+        self.area = Some(Area::new(
+            Point::new(self.cursor.pos.0, self.cursor.pos.1),
+            Point::new(self.cursor.pos.0, self.cursor.pos.1),
+        ));
+        self.lasso = vec![self.cursor.pos];
+        self.update_selection();
+        self.draw_help().ok();
+        self.draw_status();
+    }
+    ///start drawing a line or a rectangle anchored at the cursor
+    pub fn shape_mode(&mut self, mode: Mode) {
+        self.mode = mode;
         self.area = Some(Area::new(
             Point::new(self.cursor.pos.0, self.cursor.pos.1),
             Point::new(self.cursor.pos.0, self.cursor.pos.1),
@@ -79,7 +355,67 @@ impl Editor {
         self.draw_help().ok();
         self.draw_status();
         self.cursor.toogle_hidden();
+        self.draw_shape_preview();
+    }
+
+    ///the pixels covered by the shape that is being drawn
+    fn shape_points(&self) -> Vec<(usize, usize)> {
+        let (shape, area) = match (self.mode.shape(), self.get_area()) {
+            (Some(shape), Some(area)) => (shape, area),
+            _ => return vec![],
+        };
+        shape
+            .points(area.starting_point, area.final_point)
+            .into_iter()
+            .filter(|p| self.is_in_image((p.y, p.x)))
+            .map(|p| (p.y as usize, p.x as usize))
+            .collect()
+    }
+
+    fn draw_shape_preview(&self) {
+        self.attrset(COLOR_PAIR(9));
+        for (y, x) in self.shape_points() {
+            self.canvas_addch((y as i32, x as i32), '#');
+        }
+        self.attroff(COLOR_PAIR(9));
+    }
+
+    fn clear_shape_preview(&self) {
+        for pos in self.shape_points() {
+            self.redraw_pix(pos).ok();
+        }
+    }
+
+    ///move the free end of the shape to `pos`
+    pub fn set_shape_end(&mut self, pos: (i32, i32)) -> Result<()> {
+        self.clear_shape_preview();
+        self.get_mut_area()
+            .context("not drawing a shape")?
+            .set_final_point_pos(pos);
+        self.draw_shape_preview();
+        Ok(())
+    }
+
+    ///start a new shape at `pos`
+    pub fn anchor_shape(&mut self, pos: (i32, i32)) -> Result<()> {
+        self.clear_shape_preview();
+        self.set_cursor_pos(pos)?;
+        self.area = Some(Area::new(Point::new(pos.0, pos.1), Point::new(pos.0, pos.1)));
+        self.draw_shape_preview();
+        Ok(())
     }
+
+    ///paint the shape, the next one starts where this one ended
+    pub fn commit_shape(&mut self, color: PixelColor) -> Result<()> {
+        for pos in self.shape_points() {
+            self.paint_pix(pos, color)?;
+        }
+        let end = self.get_area().context("not drawing a shape")?.final_point;
+        self.area = Some(Area::new(end, end));
+        self.draw_shape_preview();
+        Ok(())
+    }
+
     pub fn get_pix(&self, pos: &(usize, usize)) -> Option<&PixelColor> {
         let pix = self.tif_image.pixels.get(pos.0)?;
         pix.get(pos.1)
@@ -92,142 +428,570 @@ impl Editor {
 
     pub fn redraw_pix(&self, pos: (usize, usize)) -> Result<()> {
         let pix = self.get_pix(&pos).context("out of bounds")?;
+        let pix = match &self.remap {
+            Some(remap) => remap.target_of(&self.pallete, pix),
+            None => *pix,
+        };
 
-        let color = Color::from(pix);
-        self.attrset(COLOR_PAIR(*color));
-        self.mvaddch(pos.0 as i32, pos.1 as i32, ' ');
-        self.attroff(COLOR_PAIR(*color));
+        let changed = self
+            .comparison
+            .as_ref()
+            .is_some_and(|comparison| comparison.is_changed(&self.tif_image, pos));
+        let outline = self
+            .selection
+            .as_ref()
+            .is_some_and(|selection| selection.is_edge(pos));
+        let (pair, c) = match self.grid.char_at(pos) {
+            _ if changed => (self.pallete.grid_pair_of(&pix), '*'),
+            //dashes over the color of the pixel, that move by one every phase
+            _ if outline => {
+                let dash = (pos.0 + pos.1 + 4 - self.outline_phase) % 4 < 2;
+                (self.pallete.grid_pair_of(&pix), if dash { ':' } else { '.' })
+            }
+            Some(c) => (self.pallete.grid_pair_of(&pix), c),
+            None => (self.pallete.pair_of(&pix), ' '),
+        };
+        self.attrset(COLOR_PAIR(pair));
+        for offset in self.tile_offsets() {
+            self.canvas_addch((pos.0 as i32 + offset.0, pos.1 as i32 + offset.1), c);
+        }
+        self.attroff(COLOR_PAIR(pair));
         Ok(())
     }
-    pub fn set_cursor_pos(&mut self, pos: (i32, i32)) -> Result<()> {
+
+    ///where the copies of the image are, relative to the canvas. only the canvas without tile mode
+    fn tile_offsets(&self) -> Vec<(i32, i32)> {
+        if !self.tile {
+            return vec![(0, 0)];
+        }
+        let (height, width) = (self.tif_image.height as i32, self.tif_image.width as i32);
+        let mut offsets = vec![];
+        for y in -1..=1 {
+            for x in -1..=1 {
+                offsets.push((y * height, x * width));
+            }
+        }
+        offsets
+    }
+
+    ///the same position inside of the image in tile mode, positions are left alone without it
+    pub fn wrap(&self, pos: (i32, i32)) -> (i32, i32) {
+        let (height, width) = (self.tif_image.height as i32, self.tif_image.width as i32);
+        if !self.tile || height == 0 || width == 0 {
+            return pos;
+        }
+        (pos.0.rem_euclid(height), pos.1.rem_euclid(width))
+    }
+
+    ///the canvas moves when the copies are shown or hidden, so everything is drawn again.
+    ///the tiles are 3 times as big as the image, nothing changes when the terminal cant fit them
+    pub fn set_tile(&mut self, tile: bool) -> Result<()> {
+        let before = std::mem::replace(&mut self.tile, tile);
+        if let Err(e) = self.is_terminal_size_enough() {
+            self.tile = before;
+            return Err(e.context("the tiles dont fit"));
+        }
+        self.redraw_all()
+    }
+
+    ///where every part of the editor goes in the current terminal
+    pub fn layout(&self) -> Layout {
+        Layout::new(
+            self.get_window_size(),
+            self.get_image_size(),
+            self.show_rulers,
+            self.tile,
+            self.pattern_list_x_pos() + PATTERNS.len() as i32 * 5 - 1,
+        )
+    }
+
+    ///screen position of the top left pixel of the image, the rulers push it away from the corner
+    pub fn canvas_origin(&self) -> (i32, i32) {
+        let canvas = self.layout().canvas;
+        (canvas.y, canvas.x)
+    }
+
+    ///image position (or a position right next to the image, like the border) to screen position
+    pub fn to_screen(&self, pos: (i32, i32)) -> (i32, i32) {
+        let origin = self.canvas_origin();
+        (pos.0 + origin.0, pos.1 + origin.1)
+    }
+
+    ///screen position to image position, the result can be out of the image.
+    ///positions on the copies of the image in tile mode are the ones they show
+    pub fn to_image(&self, pos: (i32, i32)) -> (i32, i32) {
+        let origin = self.canvas_origin();
+        let image_pos = (pos.0 - origin.0, pos.1 - origin.1);
+        if self.layout().tiles.contains(pos) {
+            return self.wrap(image_pos);
+        }
+        image_pos
+    }
+
+    fn canvas_addch(&self, pos: (i32, i32), c: char) {
+        let pos = self.to_screen(pos);
+        self.mvaddch(pos.0, pos.1, c);
+    }
+    pub fn is_in_image(&self, pos: (i32, i32)) -> bool {
         let image = self.get_image_size();
-        if !(pos.0 >= 0 && pos.0 <= image.0 as i32 - 1 && pos.1 >= 0 && pos.1 <= image.1 as i32 - 1)
-        {
+        pos.0 >= 0 && pos.0 < image.0 as i32 && pos.1 >= 0 && pos.1 < image.1 as i32
+    }
+
+    ///the closest position inside of the image, used for mouse positions that left the canvas
+    pub fn clip_to_image(&self, pos: (i32, i32)) -> (i32, i32) {
+        let image = self.get_image_size();
+        (
+            pos.0.min(image.0 as i32 - 1).max(0),
+            pos.1.min(image.1 as i32 - 1).max(0),
+        )
+    }
+
+    pub fn set_cursor_pos(&mut self, pos: (i32, i32)) -> Result<()> {
+        let pos = self.wrap(pos);
+        if !self.is_in_image(pos) {
             return Err(anyhow!("out of image bounds"));
         }
-        self.redraw_pix(self.cursor.coord_as_usize())?;
+        self.clear_cursor();
+        self.mark_paste_dirty();
         self.cursor.set_pos(pos);
-        self.cursor.draw(&*self);
-        self.refresh();
         Ok(())
     }
 
+    ///the pixels under the cursor footprint are drawn again by the next `flush`
+    fn clear_cursor(&mut self) {
+        self.dirty.extend(self.cursor.footprint(self.get_image_size()));
+    }
+
+    pub fn set_brush(&mut self, brush: Brush) {
+        self.clear_cursor();
+        self.cursor.brush = brush;
+        self.draw_status();
+    }
+
+    ///move the cursor to `pos` and paint there
+    pub fn paint_at(&mut self, pos: (i32, i32), color: PixelColor) -> Result<()> {
+        self.set_cursor_pos(pos)?;
+        self.set_pix_at_cursor(color)
+    }
+
+    ///paint a line from the cursor to `pos` and move the cursor there,
+    ///so fast mouse drags dont leave gaps
+    pub fn stroke_to(&mut self, pos: (i32, i32), color: PixelColor) -> Result<()> {
+        let from = Point::new(self.cursor.pos.0, self.cursor.pos.1);
+        let to = if self.tile {
+            //the short way, across the edge when it is closer
+            let (height, width) = (self.tif_image.height as i32, self.tif_image.width as i32);
+            let nearest = |from: i32, to: i32, size: i32| from + (to - from + size / 2).rem_euclid(size) - size / 2;
+            Point::new(nearest(from.y, pos.0, height), nearest(from.x, pos.1, width))
+        } else {
+            Point::new(pos.0, pos.1)
+        };
+        for point in shapes::line(from, to) {
+            self.brush_pix((point.y, point.x), color)?;
+        }
+        self.set_cursor_pos(pos)
+    }
+
     pub fn set_pix_at_cursor(&mut self, color: PixelColor) -> Result<()> {
-        self.set_pix_color(self.cursor.coord_as_usize(), color)?;
-        self.cursor.draw(&*self);
-        self.refresh();
+        self.brush_pix(self.cursor.pos, color)?;
         Ok(())
     }
 
+    ///paint the brush footprint centered at `pos`
+    fn brush_pix(&mut self, pos: (i32, i32), color: PixelColor) -> Result<()> {
+        for pos in self.cursor.brush.footprint(pos) {
+            let pos = self.wrap(pos);
+            if self.is_in_image(pos) {
+                let pos = (pos.0 as usize, pos.1 as usize);
+                self.paint_pix(pos, self.patterned(pos, color))?;
+            }
+        }
+        Ok(())
+    }
+
+    ///set the color of a pixel and of all its mirrored positions, the ones outside of the selection are left alone
+    fn paint_pix(&mut self, pos: (usize, usize), color: PixelColor) -> Result<()> {
+        for mirrored in self.symmetry.mirror(pos, self.get_image_size()) {
+            if self.is_selected(mirrored) {
+                self.set_pix_color(mirrored, color)?;
+            }
+        }
+        Ok(())
+    }
+
+    ///every pixel can be painted when nothing is selected
+    pub fn is_selected(&self, pos: (usize, usize)) -> bool {
+        self.selection
+            .as_ref()
+            .is_none_or(|selection| selection.contains(pos))
+    }
+
+    ///puts back the pixels outside of the selection after a change that didnt go through `paint_pix`
+    fn keep_unselected(&mut self, before: &TifImage) {
+        let Some(selection) = &self.selection else {
+            return;
+        };
+        if selection.size() != self.get_image_size() {
+            return;
+        }
+        for (y, (row, before)) in self.tif_image.pixels.iter_mut().zip(&before.pixels).enumerate() {
+            for (x, (pixel, before)) in row.iter_mut().zip(before).enumerate() {
+                if !selection.contains((y, x)) {
+                    *pixel = *before;
+                }
+            }
+        }
+    }
+
     fn set_pix_color(&mut self, pos: (usize, usize), color: PixelColor) -> Result<()> {
+        let old = *self.get_pix(&pos).context("out of bounds")?;
+        if let Some(comparison) = &mut self.comparison {
+            comparison.update(&self.tif_image, pos, color);
+        }
+        self.stats.update(&self.pallete, pos, old, color);
         let pix = self.get_mut_pix(&pos).context("out of bounds")?;
         *pix = color;
-        self.redraw_pix(pos)?;
-        self.refresh();
+        self.dirty.insert(pos);
+        self.dirty_stats = true;
         Ok(())
     }
 
+    ///counts everything again after the image changed without going through `set_pix_color`,
+    ///or after the secondary color, used as the background by the statistics, changed
+    fn image_changed(&mut self) {
+        self.stats = Stats::new(&self.tif_image, &self.pallete, self.secondary_color);
+        if let Some(comparison) = self.comparison.take() {
+            self.comparison = Some(Comparison::new(comparison.saved, &self.tif_image));
+        }
+        self.draw_stats();
+    }
+
+    ///hides the cursor for the off half of a blink, or shows it again
+    pub fn blink(&mut self, blinked: bool) {
+        if self.cursor.blinked != blinked {
+            self.cursor.blinked = blinked;
+            self.clear_cursor();
+        }
+    }
+
+    ///writes the image to its file without leaving the editor
+    pub fn autosave(&mut self) -> Result<()> {
+        export::save_tif(&self.tif_image, &self.file)?;
+        //the saved file is the image now, nothing is changed anymore
+        if self.comparison.is_some() {
+            self.comparison = Some(Comparison::new(self.tif_image.clone(), &self.tif_image));
+            //the canvas isnt drawn over the warning of a small terminal
+            if !self.too_small {
+                self.draw_image();
+            }
+        }
+        //the command or the text being typed keeps the message line
+        if !matches!(self.mode, Mode::Command | Mode::Text) {
+            self.draw_message(&format!("AUTOSAVED TO {}", self.file));
+        }
+        Ok(())
+    }
+
+    ///marks the pixels that are different from the saved file, or stops marking them
+    pub fn toggle_comparison(&mut self) {
+        if self.comparison.take().is_none() {
+            match export::open_tif(&self.file) {
+                Ok(saved) => self.comparison = Some(Comparison::new(saved, &self.tif_image)),
+                Err(e) => self.draw_message(&format!("ERROR: {:#}", e)),
+            }
+        }
+        self.draw_image();
+        self.draw_cursor();
+    }
+
+    ///(height, width) of the smallest terminal that fits the editor
+    pub fn required_size(&self) -> (i32, i32) {
+        self.layout().required_size()
+    }
+
     pub fn is_terminal_size_enough(&self) -> Result<()> {
         let term = self.get_window_size();
-        let image = self.get_image_size();
-        if term.0 < image.0 as i32 + 9 {
+        let required = self.required_size();
+        if term.0 < required.0 {
             return Err(anyhow!("terminal's height is too small"));
         }
-        if term.1 < image.1 as i32 + 10 {
+        if term.1 < required.1 {
             return Err(anyhow!("terminal's width is too small"));
         }
 
         Ok(())
     }
 
+    ///draws everything again, at start, after the terminal was resized
+    ///and after changes that move the canvas or change the size of the image
+    pub fn redraw_all(&mut self) -> Result<()> {
+        self.clear();
+        self.too_small = self.is_terminal_size_enough().is_err();
+        if self.too_small {
+            self.set_up()?;
+            self.draw_too_small();
+            return Ok(());
+        }
+        self.dirty.clear();
+        self.draw_ui()?;
+        self.draw_help().ok();
+        match self.mode {
+            Mode::Line | Mode::Rectangle => self.draw_shape_preview(),
+            Mode::Remap => self.draw_remap(),
+            Mode::Command => self.draw_command_line(),
+            Mode::Paste => self.draw_paste_preview(),
+            Mode::Text => {
+                self.draw_paste_preview();
+                self.draw_command_line();
+            }
+            Mode::Insertion | Mode::Selection | Mode::Area => {}
+        }
+        Ok(())
+    }
+
+    ///draws what changed since the last call and shows it all on the terminal at once,
+    ///the main loop calls it once per input event
+    pub fn flush(&mut self) {
+        let dirty = std::mem::take(&mut self.dirty);
+        if !self.too_small {
+            for pos in dirty {
+                self.redraw_pix(pos).ok();
+            }
+            if self.mode.shape().is_some() {
+                self.draw_shape_preview();
+            }
+            self.draw_paste_preview();
+            self.draw_cursor();
+            if std::mem::take(&mut self.dirty_stats) {
+                self.draw_stats();
+            }
+        }
+        self.refresh();
+    }
+
+    ///centered in place of the editor while the terminal is too small
+    fn draw_too_small(&self) {
+        let (rows, cols) = self.get_window_size();
+        let required = self.required_size();
+        let lines = [
+            String::from("TERMINAL TOO SMALL"),
+            format!(
+                "NEED {}X{}, HAVE {}X{}",
+                required.0, required.1, rows, cols
+            ),
+            String::from("RESIZE IT OR PRESS [Q] TO SAVE AND QUIT"),
+        ];
+        for (i, line) in lines.iter().enumerate() {
+            let x = (cols - line.len() as i32).max(0) / 2;
+            self.mvprintw(rows / 2 - 1 + i as i32, x, line);
+        }
+    }
+
     fn draw_image(&self) {
         for (height, pixels) in self.tif_image.pixels.iter().enumerate() {
-            for (width, pixel) in pixels.iter().enumerate() {
-                self.attrset(COLOR_PAIR(*Color::from(pixel)));
-                self.mvaddch(height as i32, width as i32, ' ');
-                self.attrset(COLOR_PAIR(*Color::from(pixel)));
+            for width in 0..pixels.len() {
+                self.redraw_pix((height, width)).ok();
             }
         }
     }
+    ///below and on the right of the tiles
     fn draw_border(&self) {
-        let x_pos = self.tif_image.width;
-        let y_pos = self.tif_image.height;
-        self.attrset(COLOR_PAIR(*Color::from(&PixelColor::Red)));
-        self.mvprintw(y_pos as i32, 0, String::from(" ").repeat(x_pos as usize));
-        for i in 0..y_pos {
-            self.mvaddch(i as i32, x_pos as i32, ' ');
+        let tiles = self.layout().tiles;
+        self.attrset(COLOR_PAIR(self.pallete.pair_of(&PixelColor::Red)));
+        self.mvprintw(tiles.bottom(), tiles.x, String::from(" ").repeat(tiles.width as usize));
+        for y in tiles.y..tiles.bottom() {
+            self.mvaddch(y, tiles.right(), ' ');
+        }
+        self.attroff(COLOR_PAIR(self.pallete.pair_of(&PixelColor::Red)));
+        self.draw_symmetry_axis();
+    }
+
+    ///mark where the mirror axis crosses the border, in line with the canvas
+    fn draw_symmetry_axis(&self) {
+        let tiles = self.layout().tiles;
+        let (height, width) = (self.tif_image.height as i32, self.tif_image.width as i32);
+        self.attrset(COLOR_PAIR(10));
+        if self.symmetry.mirrors_horizontally() {
+            for x in [(width - 1) / 2, width / 2] {
+                self.mvaddch(tiles.bottom(), self.to_screen((0, x)).1, '^');
+            }
         }
-        self.attroff(COLOR_PAIR(*Color::from(&PixelColor::Red)));
+        if self.symmetry.mirrors_vertically() {
+            for y in [(height - 1) / 2, height / 2] {
+                self.mvaddch(self.to_screen((y, 0)).0, tiles.right(), '<');
+            }
+        }
+        self.attroff(COLOR_PAIR(10));
     }
+    ///row of the swatches, the markers are right above
+    fn pallete_y_pos(&self) -> i32 {
+        self.layout().palette.y + 1
+    }
+
+    ///every swatch is two columns wide, with the digit that picks it below.
+    ///`vv` marks the primary color and `--` the secondary one
     fn draw_color_pallete(&self) {
-        let y_pos = self.tif_image.height + 3;
-        let mut pos = 0;
-        for px in 0..8 {
-            self.attrset(COLOR_PAIR(px));
-            self.mvprintw(y_pos as i32, pos, "  ");
-            self.attroff(COLOR_PAIR(px));
-            self.mvaddch(y_pos as i32 + 1, pos + 1, (px + 49) as u8 as char);
-            pos += 2;
+        let y_pos = self.pallete_y_pos();
+        for (slot, color) in self.pallete.colors.iter().enumerate() {
+            let pos = slot as i32 * 2;
+            let marker = if *color == self.selected_color {
+                "vv"
+            } else if *color == self.secondary_color {
+                "--"
+            } else {
+                "  "
+            };
+            self.mvprintw(y_pos - 1, pos, marker);
+            self.attrset(COLOR_PAIR(self.pallete.pair_of(color)));
+            self.mvprintw(y_pos, pos, "  ");
+            self.attroff(COLOR_PAIR(self.pallete.pair_of(color)));
+            self.mvaddch(y_pos + 1, pos + 1, char::from_digit(slot as u32 + 1, 10).unwrap_or(' '));
         }
     }
 
-    fn get_area_positions(&self) -> Result<((i32, i32), (i32, i32))> {
-        if self.mode != Mode::Area {
-            return Err(anyhow!("impossible to draw area. not in Area mode"));
+    fn pattern_list_x_pos(&self) -> i32 {
+        self.pallete.colors.len() as i32 * 2 + 2
+    }
+
+    ///every pattern is previewed with the current colors in a 4x2 box, `vvvv` marks the selected one
+    fn draw_pattern_list(&self) {
+        let y_pos = self.pallete_y_pos();
+        for (i, pattern) in PATTERNS.iter().enumerate() {
+            let x_pos = self.pattern_list_x_pos() + i as i32 * 5;
+            let marker = if *pattern == self.pattern { "vvvv" } else { "    " };
+            self.mvprintw(y_pos - 1, x_pos, marker);
+            for y in 0..2 {
+                for x in 0..4 {
+                    let color = pattern.color_at((y, x), self.selected_color, self.secondary_color);
+                    self.attrset(COLOR_PAIR(self.pallete.pair_of(&color)));
+                    self.mvaddch(y_pos + y as i32, x_pos + x as i32, ' ');
+                    self.attroff(COLOR_PAIR(self.pallete.pair_of(&color)));
+                }
+            }
         }
-        let area = self.get_area().unwrap();
-        let area_x = if area.starting_point.x >= area.final_point.x {
-            (area.final_point.x, area.starting_point.x)
-        } else {
-            (area.starting_point.x, area.final_point.x)
-        };
-        let area_y = if area.starting_point.y >= area.final_point.y {
-            (area.final_point.y, area.starting_point.y)
-        } else {
-            (area.starting_point.y, area.final_point.y)
-        };
-        Ok((area_y, area_x))
     }
-    ///redraw all the pixels that were covered by the area selection
-    pub fn draw_area_pixels(&self) -> Result<()> {
-        let area_position = self.get_area_positions()?;
-        for i in (area_position.0 .0)..=(area_position.0 .1) {
-            for j in area_position.1 .0..=area_position.1 .1 {
-                self.redraw_pix((i as usize, j as usize))?;
+
+    ///the pattern shown at the screen position `pos`, if any
+    pub fn pattern_at(&self, pos: (i32, i32)) -> Option<Pattern> {
+        let x = pos.1 - self.pattern_list_x_pos();
+        let y = pos.0 - self.pallete_y_pos();
+        if !(0..2).contains(&y) || x < 0 || x % 5 == 4 {
+            return None;
+        }
+        PATTERNS.get(x as usize / 5).copied()
+    }
+
+    ///the color of the swatch shown at the screen position `pos`, if any
+    pub fn swatch_at(&self, pos: (i32, i32)) -> Option<PixelColor> {
+        let swatches = Rect::new(self.pallete_y_pos(), 0, 1, self.pattern_list_x_pos() - 2);
+        if !swatches.contains(pos) {
+            return None;
+        }
+        self.pallete.color_at(pos.1 as usize / 2)
+    }
+
+    ///the pixels picked by the selection tool since entering Area mode
+    fn tool_selection(&self) -> Option<Selection> {
+        let area = self.get_area()?;
+        let image = self.get_image_size();
+        let cursor = (area.final_point.y, area.final_point.x);
+        Some(match self.selection_tool {
+            SelectionTool::Rectangle => {
+                Selection::rectangle(image, (area.starting_point.y, area.starting_point.x), cursor)
             }
+            SelectionTool::Lasso => Selection::lasso(image, &self.lasso),
+            SelectionTool::Wand => Selection::wand(&self.tif_image, cursor),
+        })
+    }
+
+    ///in Area mode, the selection becomes the one there was before entering it changed by the tool
+    fn update_selection(&mut self) {
+        if self.mode != Mode::Area {
+            return;
         }
-        Ok(())
+        let Some(tool_selection) = self.tool_selection() else {
+            return;
+        };
+        let base = self
+            .selection_base
+            .clone()
+            .unwrap_or_else(|| Selection::empty(self.get_image_size()));
+        self.set_selection(Some(base.combine(&tool_selection, self.combine)));
     }
 
-    pub fn draw_area(&self) -> Result<()> {
-        let area_position = self.get_area_positions()?;
-        self.attrset(COLOR_PAIR(9));
-        for i in (area_position.0 .0)..=(area_position.0 .1) {
-            for j in area_position.1 .0..=area_position.1 .1 {
-                if (i >= 0 && i <= self.tif_image.height as i32 - 1)
-                    && j >= 0
-                    && j <= self.tif_image.width as i32 - 1
-                {
-                    self.mvaddch(i, j, '#');
+    ///only the pixels where the outline appears or disappears are drawn again
+    fn set_selection(&mut self, selection: Option<Selection>) {
+        let is_edge = |selection: &Option<Selection>, pos| {
+            selection.as_ref().is_some_and(|selection| selection.is_edge(pos))
+        };
+        for y in 0..self.tif_image.height as usize {
+            for x in 0..self.tif_image.width as usize {
+                if is_edge(&self.selection, (y, x)) != is_edge(&selection, (y, x)) {
+                    self.dirty.insert((y, x));
                 }
             }
         }
-        self.attroff(COLOR_PAIR(9));
-        self.refresh();
-        Ok(())
+        //an empty selection is no selection
+        self.selection = selection.filter(|selection| selection.count() > 0);
+        self.dirty_stats = true;
+    }
+
+    ///the outline of the selection is drawn again, moved by one step
+    pub fn march(&mut self) {
+        self.outline_phase = (self.outline_phase + 1) % 4;
+        if let Some(selection) = &self.selection {
+            let edges = selection
+                .positions()
+                .into_iter()
+                .filter(|pos| selection.is_edge(*pos));
+            self.dirty.extend(edges);
+        }
+    }
+
+    pub fn select_all(&mut self) {
+        self.set_selection(Some(Selection::all(self.get_image_size())));
+    }
+
+    pub fn select_none(&mut self) {
+        self.set_selection(None);
+    }
+
+    ///nothing selected becomes everything selected
+    pub fn invert_selection(&mut self) {
+        let mut selection = self
+            .selection
+            .clone()
+            .unwrap_or_else(|| Selection::empty(self.get_image_size()));
+        selection.invert();
+        self.set_selection(Some(selection));
+    }
+
+    pub fn set_selection_tool(&mut self, tool: SelectionTool) {
+        self.selection_tool = tool;
+        self.update_selection();
+        self.draw_status();
     }
-    ///set the color of the pixels selected in the area
+
+    ///Add and Subtract are turned off by picking them again
+    pub fn toggle_combine(&mut self, combine: Combine) {
+        self.combine = if self.combine == combine {
+            Combine::Replace
+        } else {
+            combine
+        };
+        self.update_selection();
+        self.draw_status();
+    }
+
+    ///set the color of the selected pixels
     pub fn set_area_color(&mut self, color: PixelColor) -> Result<()> {
-        let area_position = self.get_area_positions()?;
-        for i in (area_position.0 .0)..=(area_position.0 .1) {
-            for j in area_position.1 .0..=area_position.1 .1 {
-                if (i >= 0 && i <= self.tif_image.height as i32 - 1)
-                    && j >= 0
-                    && j <= self.tif_image.width as i32 - 1
-                {
-                    self.set_pix_color((i as usize, j as usize), color)?;
-                }
-            }
+        let positions = self
+            .selection
+            .as_ref()
+            .map(Selection::positions)
+            .unwrap_or_default();
+        for pos in positions {
+            self.paint_pix(pos, self.patterned(pos, color))?;
         }
         Ok(())
     }
@@ -236,125 +1000,1064 @@ impl Editor {
         if self.mode != Mode::Area {
             return Err(anyhow!("impossible to draw area. not in Area mode"));
         }
-
-        let point = self.get_area().unwrap().final_point;
-        if point.x >= pos.1 || point.y >= pos.0 {
-            self.draw_area_pixels()?;
-        }
-
         self.get_mut_area().unwrap().set_final_point_pos(pos);
-        self.draw_area()?;
+        self.lasso.push(pos);
+        self.update_selection();
+        Ok(())
+    }
+
+    ///start a new area selection anchored at `pos`
+    pub fn anchor_area(&mut self, pos: (i32, i32)) -> Result<()> {
+        self.set_cursor_pos(pos)?;
+        self.area = Some(Area::new(Point::new(pos.0, pos.1), Point::new(pos.0, pos.1)));
+        self.lasso = vec![pos];
+        self.update_selection();
         Ok(())
     }
 
     pub fn set_area_based_on_current_cursor_position(&mut self) -> Result<()> {
         self.set_area_pos(self.cursor.pos)
     }
+    ///`text` on the row `row` of `rect`, padded with spaces (or cut) to the width of the rect
+    fn print_in(&self, rect: Rect, row: i32, text: &str) {
+        if !(0..rect.height).contains(&row) {
+            return;
+        }
+        let text: String = format!("{:<1$}", text, rect.width as usize)
+            .chars()
+            .take(rect.width as usize)
+            .collect();
+        //mvprintw would read a `%` as a format
+        self.mvaddstr(rect.y + row, rect.x, text);
+    }
+
     fn draw_status(&self) {
-        let pos_y = self.tif_image.height as i32 + 7;
-        self.mvprintw(
-            pos_y,
-            0,
-            format!("MODE: {:?}               ", self.get_mode()),
+        let status = self.layout().status;
+        let mode = match self.get_mode() {
+            Mode::Area => format!("Area ({:?}, {:?})", self.selection_tool, self.combine),
+            Mode::Paste => match &self.library {
+                Some(library) => format!("Paste ({})", library.names[library.selected]),
+                None => String::from("Paste (clipboard)"),
+            },
+            Mode::Text => format!("Text ({})", self.font.name()),
+            mode => format!("{:?}", mode),
+        };
+        self.print_in(status, 0, &format!("MODE: {}", mode));
+        self.print_in(
+            status,
+            1,
+            &format!(
+                "CURRENT COLOR: {:?} [{}]",
+                self.selected_color,
+                self.pallete.slot_of(&self.selected_color) + 1
+            ),
+        );
+        self.print_in(
+            status,
+            2,
+            &format!(
+                "SECONDARY COLOR: {:?} [{}]",
+                self.secondary_color,
+                self.pallete.slot_of(&self.secondary_color) + 1
+            ),
         );
-        self.mvprintw(
-            pos_y + 1,
-            0,
-            format!("CURRENT COLOR: {:?}         ", self.selected_color),
+        self.print_in(
+            status,
+            3,
+            &format!(
+                "MIRROR: {:?}  TILE: {}",
+                self.symmetry,
+                if self.tile { "on" } else { "off" }
+            ),
         );
+        self.print_in(
+            status,
+            4,
+            &format!(
+                "BRUSH: {} {:?}",
+                self.cursor.brush.size, self.cursor.brush.shape
+            ),
+        );
+        self.print_in(
+            status,
+            5,
+            &format!(
+                "PATTERN: {:?}  GRID: {}",
+                self.pattern,
+                if self.grid.visible {
+                    self.grid.size.to_string()
+                } else {
+                    String::from("off")
+                }
+            ),
+        );
+        self.draw_cursor_info();
+    }
+
+    ///position of the cursor and the color under it, below the rest of the status
+    fn draw_cursor_info(&self) {
+        let under = self
+            .get_pix(&self.cursor.coord_as_usize())
+            .map(|color| format!("{:?}", color))
+            .unwrap_or_default();
+        let changed = self
+            .comparison
+            .as_ref()
+            .map(|comparison| format!("  CHANGED: {}", comparison.changed))
+            .unwrap_or_default();
+        self.print_in(
+            self.layout().status,
+            6,
+            &format!(
+                "CURSOR: ({}, {}) UNDER: {}{}",
+                self.cursor.pos.0, self.cursor.pos.1, under, changed
+            ),
+        );
+    }
+
+    ///column indices above the canvas and row indices on its left
+    fn draw_rulers(&self) {
+        if !self.show_rulers {
+            return;
+        }
+        let origin = self.canvas_origin();
+        //the copies on the left in tile mode are between the row indices and the canvas
+        let left = self.layout().tiles.x;
+        let (labels, digits) = grid::top_ruler(self.tif_image.width);
+        self.mvprintw(0, origin.1, labels);
+        self.mvprintw(1, origin.1, digits);
+        for y in 0..self.tif_image.height as i32 {
+            self.mvprintw(
+                origin.0 + y,
+                0,
+                format!("{:>width$}", y, width = left as usize - 1),
+            );
+        }
+    }
+
+    ///the canvas moves when the rulers are toggled, so everything is drawn again
+    pub fn set_rulers(&mut self, show_rulers: bool) -> Result<()> {
+        self.show_rulers = show_rulers;
+        self.redraw_all()
+    }
+
+    pub fn set_grid(&mut self, grid: Grid) {
+        self.grid = grid;
+        self.draw_image();
+        self.draw_cursor();
+        self.draw_status();
+    }
+
+    pub fn command_mode(&mut self) {
+        self.command_line.clear();
+        self.set_mode(Mode::Command);
+        self.draw_command_line();
+    }
+
+    ///`c` is a key typed in Command mode
+    pub fn edit_command_line(&mut self, c: char) -> Result<()> {
+        match c {
+            '\x1b' => self.set_mode(Mode::Selection),
+            '\n' | '\r' => self.run_command_line()?,
+            '\x7f' | '\x08' => {
+                self.command_line.pop();
+            }
+            c if !c.is_control() => self.command_line.push(c),
+            _ => {}
+        }
+        if self.mode == Mode::Command {
+            self.draw_command_line();
+        }
+        Ok(())
+    }
+
+    ///runs the typed command, errors are shown on the message line instead of closing the editor
+    fn run_command_line(&mut self) -> Result<()> {
+        let line = std::mem::take(&mut self.command_line);
+        self.set_mode(Mode::Selection);
+        let before = self.tif_image.clone();
+        //the commands that use the selection, the others work on the image only
+        let result = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["delete"] => self.delete_selection(),
+            ["crop"] => self.crop_to_selection(),
+            ["export", path] => self.export_selection(path),
+            ["delete" | "crop", ..] => Err(anyhow!("usage: {}", line.trim())),
+            ["export", ..] => Err(anyhow!("usage: export path")),
+            _ => command::run_line(&line, &mut self.tif_image, &self.file)
+                .map(|()| self.keep_unselected(&before)),
+        };
+        //the command might have resized the image or saved it
+        if self.comparison.is_some() {
+            self.comparison = export::open_tif(&self.file)
+                .ok()
+                .map(|saved| Comparison::new(saved, &self.tif_image));
+        }
+        self.image_changed();
+        //the command might have changed any pixel or the size of the image
+        self.cursor.pos = self.clip_to_image(self.cursor.pos);
+        if self
+            .selection
+            .as_ref()
+            .is_some_and(|selection| selection.size() != self.get_image_size())
+        {
+            self.selection = None;
+        }
+        self.redraw_all()?;
+        if self.too_small {
+            return Ok(());
+        }
+        match result {
+            Ok(()) => self.draw_message(&format!("DONE: {}", line.trim())),
+            Err(e) => self.draw_message(&format!("ERROR: {:#}", e)),
+        }
+        Ok(())
+    }
+
+    pub fn copy_selection(&mut self) -> Result<()> {
+        let selection = self.selection.as_ref().context("nothing is selected")?;
+        self.clipboard = Stamp::from_selection(&self.tif_image, selection);
+        Ok(())
+    }
+
+    ///the selection is dropped, or the copy could only be stamped over the pixels it was copied from
+    pub fn paste_clipboard(&mut self) -> Result<()> {
+        self.paste = Some(self.clipboard.clone().context("the clipboard is empty")?);
+        self.select_none();
+        self.paste_mode();
+        Ok(())
+    }
+
+    ///browse the stamps of `stamps_dir`, the pixels of the secondary color are transparent
+    pub fn open_library(&mut self) -> Result<()> {
+        let library = Library::open(&self.stamps_dir)?;
+        self.paste = Some(library.load(self.secondary_color)?);
+        self.library = Some(library);
+        self.paste_mode();
+        Ok(())
+    }
+
+    ///the previous (negative `offset`) or the next stamp of the library
+    pub fn select_stamp(&mut self, offset: i32) -> Result<()> {
+        let Some(library) = &mut self.library else {
+            return Ok(());
+        };
+        library.select(offset);
+        let stamp = library.load(self.secondary_color);
+        self.mark_paste_dirty();
+        //a stamp that cant be read is skipped, the library stays open
+        self.paste = stamp.as_ref().ok().cloned();
+        self.draw_status();
+        self.draw_help().ok();
+        stamp.map(|_| ())
+    }
+
+    fn paste_mode(&mut self) {
+        self.set_mode(Mode::Paste);
+        self.cursor.toogle_hidden();
+    }
+
+    ///the pixels under what is being pasted are drawn again by the next `flush`
+    fn mark_paste_dirty(&mut self) {
+        let Some(paste) = &self.paste else {
+            return;
+        };
+        let covered: Vec<_> = paste
+            .pixels_at(self.cursor.pos)
+            .into_iter()
+            .filter(|(pos, _)| self.is_in_image(*pos))
+            .map(|(pos, _)| (pos.0 as usize, pos.1 as usize))
+            .collect();
+        self.dirty.extend(covered);
+    }
+
+    ///what is being pasted, with `+` over its colors so it cant be mistaken for the image
+    fn draw_paste_preview(&self) {
+        let Some(paste) = &self.paste else {
+            return;
+        };
+        for (pos, color) in paste.pixels_at(self.cursor.pos) {
+            if self.is_in_image(pos) {
+                self.attrset(COLOR_PAIR(self.pallete.grid_pair_of(&color)));
+                self.canvas_addch(pos, '+');
+                self.attroff(COLOR_PAIR(self.pallete.grid_pair_of(&color)));
+            }
+        }
+    }
+
+    ///paints what is being pasted at the cursor, only inside of the selection
+    pub fn stamp(&mut self) -> Result<()> {
+        let Some(paste) = &self.paste else {
+            return Ok(());
+        };
+        for (pos, color) in paste.pixels_at(self.cursor.pos) {
+            if self.is_in_image(pos) && self.is_selected((pos.0 as usize, pos.1 as usize)) {
+                self.set_pix_color((pos.0 as usize, pos.1 as usize), color)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn text_mode(&mut self) {
+        self.set_mode(Mode::Text);
+        self.cursor.toogle_hidden();
+        self.update_text();
+    }
+
+    ///`c` is a key typed in Text mode
+    pub fn edit_text(&mut self, c: char) -> Result<()> {
+        match c {
+            '\x1b' => {
+                self.set_mode(Mode::Selection);
+                return Ok(());
+            }
+            //draws the text, another one can be typed right away
+            '\n' | '\r' => {
+                self.stamp()?;
+                self.text.clear();
+            }
+            '\t' => {
+                self.font = self.font.next();
+                self.draw_status();
+            }
+            '\x7f' | '\x08' => {
+                self.text.pop();
+            }
+            c if !c.is_control() => self.text.push(c),
+            _ => {}
+        }
+        self.update_text();
+        Ok(())
+    }
+
+    ///the preview follows the text, the font and the selected color
+    fn update_text(&mut self) {
+        self.mark_paste_dirty();
+        self.paste = Some(self.font.render(&self.text, self.selected_color));
+        self.draw_command_line();
+    }
+
+    ///the selected pixels get the secondary color
+    pub fn delete_selection(&mut self) -> Result<()> {
+        let selection = self.selection.as_ref().context("nothing is selected")?;
+        for pos in selection.positions() {
+            self.set_pix_color(pos, self.secondary_color)?;
+        }
+        Ok(())
+    }
+
+    ///the image becomes the smallest rectangle around the selection
+    fn crop_to_selection(&mut self) -> Result<()> {
+        let bounds = self
+            .selection
+            .as_ref()
+            .and_then(Selection::bounds)
+            .context("nothing is selected")?;
+        self.tif_image = selection::crop(&self.tif_image, bounds);
+        self.selection = None;
+        Ok(())
+    }
+
+    ///writes the selected pixels to `path`, the format is picked from the extension.
+    ///inside of the bounds of the selection, the pixels that arent selected get the secondary color
+    fn export_selection(&self, path: &str) -> Result<()> {
+        let extracted = self
+            .selection
+            .as_ref()
+            .and_then(|selection| selection.extract(&self.tif_image, self.secondary_color))
+            .context("nothing is selected")?;
+        export::export(&extracted, &self.pallete, path)
+    }
+
+    ///the last row, used by the command prompt, its results and the warnings of the editor
+    pub fn draw_message(&self, message: &str) {
+        if self.too_small {
+            return;
+        }
+        //padded so the previous message is erased
+        self.print_in(self.layout().message, 0, message);
+    }
+
+    fn draw_command_line(&self) {
+        match self.mode {
+            Mode::Command => self.draw_message(&format!(":{}", self.command_line)),
+            Mode::Text => self.draw_message(&format!("TEXT: {}", self.text)),
+            _ => self.draw_message(""),
+        }
     }
 
     fn draw_cursor(&self) {
-        self.cursor.draw(&*self);
+        self.cursor
+            .draw(self, self.get_image_size(), self.canvas_origin());
+        self.draw_cursor_info();
+    }
+
+    ///colors and input settings of the terminal
+    fn set_up(&self) -> Result<()> {
+        if let Screen::Terminal(window) = &self.screen {
+            color::set_editor_up(window, &self.pallete)?;
+        }
+        Ok(())
     }
 
     pub fn draw_ui(&self) -> Result<()> {
-        color::set_editor_up(&*self)?;
+        self.set_up()?;
+        self.draw_rulers();
         self.draw_image();
         self.draw_border();
         self.draw_color_pallete();
+        self.draw_pattern_list();
         self.draw_cursor();
         self.draw_status();
-        self.refresh();
 
         Ok(())
     }
 
+    fn help_lines(&self) -> &'static [&'static str] {
+        match self.mode {
+            Mode::Selection => &[
+                "[I] -> insertion mode",
+                "[S] -> Area Mode",
+                "[1..8] -> select color",
+                "[M] / [T] -> mirror / tile mode",
+                "[E] / right click -> pick color",
+                "[X] -> swap primary and secondary",
+                "[L] / [R] -> line / rectangle",
+                "[+ -] / [B] -> brush size / shape",
+                "[P] / [F] -> pattern / bucket fill",
+                "[C] / [SHIFT+C] -> replace / swap colors",
+                "[G] / [SHIFT+G] -> grid / grid size",
+                "[A] / [N] / [V] -> all / none / invert",
+                "[DEL] -> delete the selection",
+                "[CTRL+C] / [CTRL+V] -> copy / paste",
+                "[O] / [SHIFT+T] -> stamp library / text",
+                "[SHIFT+R] / [:] -> rulers / command",
+                "[SHIFT+D] / [TAB] -> compare / statistics",
+            ],
+            Mode::Insertion => &[
+                "[ESC] -> selection mode",
+                "[SPACE] -> paint the area",
+                "[ARROW KEYS] -> move without painting",
+                "[wasd] -> move and paint",
+                "[E] / ctrl + click -> pick color",
+                "[X] -> swap primary and secondary",
+                "left / right drag -> paint",
+                "[+ -] / [B] -> brush size / shape",
+                "[P] / [F] -> pattern / bucket fill",
+                "[C] -> replace color under cursor",
+            ],
+            Mode::Area => &[
+                "[ESC] / [ENTER] -> keep the selection",
+                "[SPACE] -> paint the selection",
+                "[WASD] -> move and select",
+                "drag -> select with the mouse",
+                "[T] -> rectangle / lasso / wand",
+                "[+] / [-] -> add to / subtract from it",
+                "[C] -> replace color in the selection",
+                "[DEL] -> delete the selection",
+            ],
+            Mode::Line | Mode::Rectangle => &[
+                "[ESC] -> selection mode",
+                "[SPACE] -> draw the shape",
+                "[WASD] -> move the end of the shape",
+                "drag -> draw with the mouse",
+            ],
+            Mode::Remap => &[
+                "[ESC] -> cancel",
+                "[1..8] -> new color of the slot",
+                "[A D] -> previous / next slot",
+                "[SPACE] -> swap the colors",
+            ],
+            Mode::Paste => &[
+                "[ESC] -> selection mode",
+                "[SPACE] / click -> stamp",
+                "[WASD] / drag -> move",
+                "[[ ]] -> previous / next stamp",
+            ],
+            Mode::Text => &[
+                "[ESC] -> selection mode",
+                "[ENTER] -> draw the text",
+                "[ARROW KEYS] / click -> move",
+                "[TAB] -> 3x5 / 5x7 font",
+                "[BACKSPACE] -> delete a letter",
+            ],
+            Mode::Command => &[
+                "[ESC] -> cancel",
+                "[ENTER] -> run the command",
+                "pixel y x color",
+                "fill / line / rect y1 x1 y2 x2 color",
+                "flood y x color",
+                "replace from_color to_color",
+                "resize height width [color]",
+                "save [path]",
+                "delete / crop (the selection)",
+                "export path (the selection)",
+            ],
+        }
+    }
+
     pub fn draw_help(&self) -> Result<()> {
-        if self.tif_image.width as i32 + 50 > self.get_window_size().1 {
-            return Err(anyhow!("window is too small"));
+        let panel = self
+            .layout()
+            .panel
+            .ok_or_else(|| anyhow!("window is too small"))?;
+        for row in 0..panel.height {
+            self.print_in(panel, row, "");
         }
-        for j in 0..10 {
-            for i in self.tif_image.width as i32 + 10..self.tif_image.width as i32 + 50 {
-                self.mvaddch(j, i, ' ');
-            }
+        if self.show_stats {
+            self.draw_stats();
+            return Ok(());
         }
-        match self.mode {
-            Mode::Selection => {
-                self.mvprintw(
-                    1,
-                    self.tif_image.width as i32 + 10,
-                    "[I] -> insertion mode".to_uppercase(),
-                );
-                self.mvprintw(
-                    3,
-                    self.tif_image.width as i32 + 10,
-                    "[S] -> Area Mode".to_uppercase(),
-                );
-                self.mvprintw(
-                    5,
-                    self.tif_image.width as i32 + 10,
-                    "[1..8] -> select color".to_uppercase(),
-                );
-            }
-            Mode::Insertion => {
-                self.mvprintw(
-                    1,
-                    self.tif_image.width as i32 + 10,
-                    "[ESC] -> selection mode".to_uppercase(),
-                );
-                self.mvprintw(
-                    3,
-                    self.tif_image.width as i32 + 10,
-                    "[SPACE] -> paint the area".to_uppercase(),
-                );
-                self.mvprintw(
-                    5,
-                    self.tif_image.width as i32 + 10,
-                    "[⬅ ➡ ⬆ ⬇] -> move without painting".to_uppercase(),
-                );
-                self.mvprintw(
-                    7,
-                    self.tif_image.width as i32 + 10,
-                    "[wasd] -> move and paint".to_uppercase(),
-                );
-            }
-            Mode::Area => {
-                self.mvprintw(
-                    1,
-                    self.tif_image.width as i32 + 10,
-                    "[ESC] -> selection mode".to_uppercase(),
-                );
-                self.mvprintw(
-                    3,
-                    self.tif_image.width as i32 + 10,
-                    "[SPACE] -> draw in the selected area".to_uppercase(),
-                );
-                self.mvprintw(
-                    5,
-                    self.tif_image.width as i32 + 10,
-                    "[WASD] -> move and sellect".to_uppercase(),
-                );
-
-            }
+        let mut lines: Vec<String> = self.help_lines().iter().map(|line| line.to_uppercase()).collect();
+        lines.extend(self.library_lines());
+        //an empty row between the lines when there is room for it
+        let spacing = if 2 * (lines.len() as i32) < panel.height { 2 } else { 1 };
+        for (i, line) in lines.iter().enumerate() {
+            self.print_in(panel, 1 + spacing * i as i32, line);
         }
         Ok(())
     }
+
+    ///the stamps around the selected one, below the help of Paste mode
+    fn library_lines(&self) -> Vec<String> {
+        const SHOWN: usize = 12;
+        let Some(library) = &self.library else {
+            return vec![];
+        };
+        let first = library.selected.saturating_sub(SHOWN / 2);
+        let mut lines = vec![format!("STAMPS IN {}:", library.dir)];
+        for (i, name) in library.names.iter().enumerate().skip(first).take(SHOWN) {
+            let marker = if i == library.selected { ">" } else { " " };
+            lines.push(format!("{} {}", marker, name));
+        }
+        lines
+    }
+
+    pub fn set_show_stats(&mut self, show_stats: bool) {
+        self.show_stats = show_stats;
+        self.draw_help().ok();
+    }
+
+    fn stats_lines(&self) -> Vec<String> {
+        let total = self.stats.total().max(1);
+        let mut lines = vec![String::from("STATISTICS")];
+        for (slot, count) in self.stats.counts.iter().enumerate() {
+            lines.push(format!(
+                "[{}] {:<8} {:>6} {:>6.1}%",
+                slot + 1,
+                format!("{:?}", self.pallete.colors[slot]).to_uppercase(),
+                count,
+                *count as f64 * 100.0 / total as f64
+            ));
+        }
+        lines.push(format!("BACKGROUND: {:?}", self.stats.background).to_uppercase());
+        lines.push(match self.stats.content_bounds() {
+            Some(((top, left), (bottom, right))) => format!(
+                "CONTENT: ({}, {}) TO ({}, {}) {}X{}",
+                top,
+                left,
+                bottom,
+                right,
+                bottom - top + 1,
+                right - left + 1
+            ),
+            None => String::from("CONTENT: NONE"),
+        });
+        if let Some(selection) = &self.selection {
+            let ((top, left), (bottom, right)) = selection.bounds().unwrap_or_default();
+            lines.push(format!(
+                "SELECTION: {}X{} ({} PIXELS)",
+                bottom - top + 1,
+                right - left + 1,
+                selection.count()
+            ));
+        }
+        lines
+    }
+
+    ///drawn over the help, every line is padded to erase the previous statistics
+    fn draw_stats(&self) {
+        let Some(panel) = self.layout().panel.filter(|_| self.show_stats) else {
+            return;
+        };
+        let lines = self.stats_lines();
+        for (i, line) in lines.iter().enumerate() {
+            self.print_in(panel, 1 + i as i32, line);
+        }
+        //the selection line disappears with the selection
+        self.print_in(panel, 1 + lines.len() as i32, "");
+    }
 }
 
 impl Deref for Editor {
-    type Target = Window;
+    type Target = Screen;
     fn deref(&self) -> &Self::Target {
-        &self.window
+        &self.screen
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::black;
+    use crate::command::Command;
+    use crate::diff;
+    use crate::layout::PANEL_WIDTH;
+    use proptest::prelude::*;
+
+    const COLORS: [PixelColor; 8] = [
+        PixelColor::Black,
+        PixelColor::Red,
+        PixelColor::Green,
+        PixelColor::Yellow,
+        PixelColor::Blue,
+        PixelColor::Magenta,
+        PixelColor::Cyan,
+        PixelColor::White,
+    ];
+
+    fn color() -> impl Strategy<Value = PixelColor> {
+        prop::sample::select(COLORS.to_vec())
+    }
+
+    fn image() -> impl Strategy<Value = TifImage> {
+        (1..12u64, 1..=40u8).prop_flat_map(|(height, width)| {
+            prop::collection::vec(
+                prop::collection::vec(color(), width as usize),
+                height as usize,
+            )
+            .prop_map(move |pixels| TifImage {
+                height,
+                width,
+                pixels,
+            })
+        })
+    }
+
+    ///positions are wrapped into the image when the operation runs
+    fn pos() -> impl Strategy<Value = (i32, i32)> {
+        (0..255i32, 0..255i32)
+    }
+
+    #[derive(Clone, Debug)]
+    enum Op {
+        Paint((i32, i32), PixelColor),
+        Stroke((i32, i32), PixelColor),
+        Bucket((i32, i32), PixelColor),
+        Replace((i32, i32), PixelColor),
+        Area((i32, i32), (i32, i32), PixelColor),
+        Shape(Mode, (i32, i32), (i32, i32), PixelColor),
+        Secondary(PixelColor),
+        BiggerBrush,
+        NextBrushShape,
+        NextSymmetry,
+        NextPattern,
+    }
+
+    fn op() -> impl Strategy<Value = Op> {
+        prop_oneof![
+            (pos(), color()).prop_map(|(pos, color)| Op::Paint(pos, color)),
+            (pos(), color()).prop_map(|(pos, color)| Op::Stroke(pos, color)),
+            (pos(), color()).prop_map(|(pos, color)| Op::Bucket(pos, color)),
+            (pos(), color()).prop_map(|(pos, color)| Op::Replace(pos, color)),
+            (pos(), pos(), color()).prop_map(|(from, to, color)| Op::Area(from, to, color)),
+            (prop::sample::select(vec![Mode::Line, Mode::Rectangle]), pos(), pos(), color())
+                .prop_map(|(mode, from, to, color)| Op::Shape(mode, from, to, color)),
+            color().prop_map(Op::Secondary),
+            Just(Op::BiggerBrush),
+            Just(Op::NextBrushShape),
+            Just(Op::NextSymmetry),
+            Just(Op::NextPattern),
+        ]
+    }
+
+    fn headless_sized(tif_image: TifImage, height: i32, width: i32) -> Editor {
+        let mut editor = Editor::with_screen(
+            Screen::headless(height, width),
+            tif_image,
+            Pallete::new(),
+            String::from("unused.tif"),
+        );
+        editor.redraw_all().unwrap();
+        editor
+    }
+
+    fn headless(tif_image: TifImage) -> Editor {
+        headless_sized(tif_image, 50, 150)
+    }
+
+    fn wrap(editor: &Editor, (y, x): (i32, i32)) -> (i32, i32) {
+        let (height, width) = editor.get_image_size();
+        (y % height as i32, x % width as i32)
+    }
+
+    fn apply(editor: &mut Editor, op: Op) -> Result<()> {
+        match op {
+            Op::Paint(pos, color) => editor.paint_at(wrap(editor, pos), color)?,
+            Op::Stroke(pos, color) => editor.stroke_to(wrap(editor, pos), color)?,
+            Op::Bucket(pos, color) => {
+                editor.set_cursor_pos(wrap(editor, pos))?;
+                editor.bucket_fill(color)?;
+            }
+            Op::Replace(pos, color) => {
+                editor.set_cursor_pos(wrap(editor, pos))?;
+                editor.replace_color_at_cursor(color)?;
+            }
+            Op::Area(from, to, color) => {
+                editor.area_mode();
+                editor.anchor_area(wrap(editor, from))?;
+                editor.set_cursor_pos(wrap(editor, to))?;
+                editor.set_area_based_on_current_cursor_position()?;
+                editor.set_area_color(color)?;
+                editor.set_mode(Mode::Selection);
+            }
+            Op::Shape(mode, from, to, color) => {
+                editor.shape_mode(mode);
+                editor.anchor_shape(wrap(editor, from))?;
+                editor.set_cursor_pos(wrap(editor, to))?;
+                editor.set_shape_end(wrap(editor, to))?;
+                editor.commit_shape(color)?;
+                editor.set_mode(Mode::Selection);
+            }
+            Op::Secondary(color) => editor.set_secondary_color(color),
+            Op::BiggerBrush => editor.set_brush(editor.cursor.brush.bigger()),
+            Op::NextBrushShape => {
+                let brush = editor.cursor.brush;
+                editor.set_brush(Brush {
+                    shape: brush.shape.next(),
+                    ..brush
+                });
+            }
+            Op::NextSymmetry => editor.set_symmetry(editor.symmetry.next()),
+            Op::NextPattern => editor.set_pattern(editor.pattern.next()),
+        }
+        editor.flush();
+        Ok(())
+    }
+
+    proptest! {
+        #[test]
+        fn save_round_trip(tif_image in image()) {
+            let parsed = TifImage::parse_from_bytes(tif_image.save()).unwrap();
+            prop_assert_eq!((parsed.height, parsed.width), (tif_image.height, tif_image.width));
+            prop_assert_eq!(parsed.pixels, tif_image.pixels);
+        }
+
+        #[test]
+        fn edits_round_trip(tif_image in image(), ops in prop::collection::vec(op(), 1..20)) {
+            let mut editor = headless(tif_image.clone());
+            editor.comparison = Some(Comparison::new(tif_image.clone(), &editor.tif_image));
+            for op in ops {
+                apply(&mut editor, op).unwrap();
+            }
+            let edited = &editor.tif_image;
+            let parsed = TifImage::parse_from_bytes(edited.save()).unwrap();
+            prop_assert_eq!((parsed.height, parsed.width), (edited.height, edited.width));
+            prop_assert_eq!(&parsed.pixels, &edited.pixels);
+
+            //the counts kept pixel by pixel match counting everything again
+            let fresh = Stats::new(edited, &editor.pallete, editor.secondary_color);
+            prop_assert_eq!(&editor.stats.counts, &fresh.counts);
+            prop_assert_eq!(editor.stats.content_bounds(), fresh.content_bounds());
+            let changed = diff::changed_pixels(&tif_image, edited).len();
+            prop_assert_eq!(editor.comparison.as_ref().unwrap().changed, changed);
+        }
+
+        #[test]
+        fn fill_command_matches_area_fill(
+            tif_image in image(),
+            from in pos(),
+            to in pos(),
+            color in color(),
+        ) {
+            let mut editor = headless(tif_image.clone());
+            let (from, to) = (wrap(&editor, from), wrap(&editor, to));
+            apply(&mut editor, Op::Area(from, to, color)).unwrap();
+
+            let mut filled = tif_image;
+            let line = format!("fill {} {} {} {} {:?}", from.0, from.1, to.0, to.1, color);
+            Command::parse(&line).unwrap().unwrap().run(&mut filled, "unused.tif").unwrap();
+            prop_assert_eq!(&filled.pixels, &editor.tif_image.pixels);
+        }
+
+        #[test]
+        fn tools_stay_inside_of_the_selection(
+            tif_image in image(),
+            from in pos(),
+            to in pos(),
+            mut ops in prop::collection::vec(op(), 1..20),
+        ) {
+            let mut editor = headless(tif_image.clone());
+            let (from, to) = (wrap(&editor, from), wrap(&editor, to));
+            let selection = Selection::rectangle(editor.get_image_size(), from, to);
+            editor.set_selection(Some(selection.clone()));
+            //a new area replaces the selection
+            ops.retain(|op| !matches!(op, Op::Area(..)));
+            for op in ops {
+                apply(&mut editor, op).unwrap();
+            }
+            let (height, width) = editor.get_image_size();
+            editor.command_line = format!("fill 0 0 {} {} red", height - 1, width - 1);
+            editor.run_command_line().unwrap();
+            //the message line tells if the command itself worked
+            prop_assert!(editor.line(49).starts_with("DONE: fill"), "{}", editor.line(49));
+            for (y, row) in tif_image.pixels.iter().enumerate() {
+                for (x, pixel) in row.iter().enumerate() {
+                    if selection.contains((y, x)) {
+                        prop_assert_eq!(editor.tif_image.pixels[y][x], PixelColor::Red);
+                    } else {
+                        prop_assert_eq!(*pixel, editor.tif_image.pixels[y][x]);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn canvas_shows_the_cursor() {
+        let editor = headless(black(2, 20));
+        assert_eq!(&editor.line(0)[..3], "#  ");
+        //the status starts 7 rows below the image
+        assert!(editor.line(2 + 7).starts_with("MODE: Selection"));
+    }
+
+    #[test]
+    fn selection_outline_is_drawn_after_a_flush() {
+        let mut editor = headless(black(3, 5));
+        editor.area_mode();
+        editor.set_cursor_pos((2, 3)).unwrap();
+        editor.set_area_based_on_current_cursor_position().unwrap();
+        assert_eq!(&editor.line(2)[..5], "     ");
+        editor.flush();
+        //only the edges get the outline, the cursor is drawn over it
+        assert_eq!(&editor.line(0)[..5], "::.. ");
+        assert_eq!(&editor.line(1)[..5], ":  : ");
+        assert_eq!(&editor.line(2)[..5], "..:# ");
+        //the pixels that leave the selection lose the outline
+        editor.set_cursor_pos((1, 1)).unwrap();
+        editor.set_area_based_on_current_cursor_position().unwrap();
+        editor.flush();
+        assert_eq!(&editor.line(0)[..5], "::   ");
+        assert_eq!(&editor.line(1)[..5], ":#   ");
+        assert_eq!(&editor.line(2)[..5], "     ");
+        //it moves
+        editor.march();
+        editor.flush();
+        assert_eq!(&editor.line(0)[..5], ".:   ");
+    }
+
+    #[test]
+    fn subtract_from_the_whole_image() {
+        let mut editor = headless(black(4, 4));
+        editor.select_all();
+        editor.set_cursor_pos((1, 1)).unwrap();
+        editor.area_mode();
+        editor.toggle_combine(Combine::Subtract);
+        editor.set_cursor_pos((2, 2)).unwrap();
+        editor.set_area_based_on_current_cursor_position().unwrap();
+        editor.set_mode(Mode::Selection);
+        let selection = editor.selection.clone().unwrap();
+        assert_eq!(selection.count(), 12);
+        assert!(!selection.contains((1, 2)));
+        editor.invert_selection();
+        assert_eq!(editor.selection.as_ref().unwrap().count(), 4);
+        editor.set_area_color(PixelColor::Red).unwrap();
+        assert_eq!(editor.stats.counts[editor.pallete.slot_of(&PixelColor::Red)], 4);
+    }
+
+    #[test]
+    fn delete_export_and_crop_the_selection() {
+        let mut editor = headless(black(4, 6));
+        editor.command_line = String::from("crop");
+        editor.run_command_line().unwrap();
+        //the message line is the last row
+        assert!(editor.line(49).starts_with("ERROR: nothing is selected"));
+
+        editor.set_selection(Some(Selection::lasso((4, 6), &[(1, 1), (1, 3), (3, 1)])));
+        editor.delete_selection().unwrap();
+        assert_eq!(editor.tif_image.pixels[1][1..4], [PixelColor::White; 3]);
+        assert_eq!(editor.tif_image.pixels[2][3], PixelColor::Black);
+
+        let path = std::env::temp_dir().join("tif_editor_export_selection.tif");
+        let path = path.to_str().unwrap();
+        editor.command_line = format!("export {}", path);
+        editor.run_command_line().unwrap();
+        let exported = export::open_tif(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        //the pixel that isnt selected gets the secondary color too
+        assert_eq!((exported.height, exported.width), (3, 3));
+        assert_eq!(exported.pixels[1], [PixelColor::White; 3]);
+
+        editor.command_line = String::from("crop");
+        editor.run_command_line().unwrap();
+        assert_eq!(editor.get_image_size(), (3, 3));
+        assert_eq!(editor.tif_image.pixels[1][2], PixelColor::Black);
+        assert!(editor.selection.is_none());
+    }
+
+    #[test]
+    fn paste_the_copied_selection() {
+        let mut tif_image = black(4, 6);
+        tif_image.pixels[0][1] = PixelColor::Red;
+        tif_image.pixels[3][4] = PixelColor::Blue;
+        let mut editor = headless(tif_image);
+        assert!(editor.paste_clipboard().is_err());
+        //the black pixel at (1, 0) isnt selected, it is transparent
+        editor.set_selection(Some(Selection::lasso((4, 6), &[(0, 0), (0, 1), (1, 1)])));
+        editor.copy_selection().unwrap();
+        editor.paste_clipboard().unwrap();
+        assert!(editor.selection.is_none());
+        editor.set_cursor_pos((2, 3)).unwrap();
+        editor.flush();
+        //previewed without changing the image
+        assert_eq!(&editor.line(2)[..6], "   ++ ");
+        assert_eq!(&editor.line(3)[..6], "    + ");
+        assert_eq!(editor.tif_image.pixels[2][4], PixelColor::Black);
+        editor.stamp().unwrap();
+        editor.set_mode(Mode::Selection);
+        editor.flush();
+        assert_eq!(&editor.line(2)[..6], "   #  ");
+        assert_eq!(editor.tif_image.pixels[2][3..5], [PixelColor::Black, PixelColor::Red]);
+        assert_eq!(editor.tif_image.pixels[3][3..5], [PixelColor::Black, PixelColor::Black]);
+    }
+
+    #[test]
+    fn typed_text_is_previewed_then_drawn() {
+        let tif_image = TifImage {
+            height: 8,
+            width: 12,
+            pixels: vec![vec![PixelColor::White; 12]; 8],
+        };
+        let mut editor = headless(tif_image);
+        editor.set_cursor_pos((1, 2)).unwrap();
+        editor.text_mode();
+        for c in "hix\x7f".chars() {
+            editor.edit_text(c).unwrap();
+        }
+        editor.flush();
+        assert_eq!(&editor.line(1)[..9], "  + + +++");
+        assert_eq!(&editor.line(2)[..9], "  + +  + ");
+        assert_eq!(editor.line(49).trim_end(), "TEXT: hi");
+        assert_eq!(editor.tif_image.pixels[1][2], PixelColor::White);
+        let path = std::env::temp_dir().join("tif_editor_autosave_text.tif");
+        editor.file = path.to_string_lossy().into_owned();
+        editor.autosave().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(editor.line(49).trim_end(), "TEXT: hi");
+        editor.edit_text('\t').unwrap();
+        assert_eq!(editor.font, Font::Large);
+        editor.edit_text('\n').unwrap();
+        assert_eq!(editor.text, "");
+        //the top row of the 5x7 H
+        assert_eq!(editor.tif_image.pixels[1][2..8], [
+            PixelColor::Black,
+            PixelColor::White,
+            PixelColor::White,
+            PixelColor::White,
+            PixelColor::Black,
+            PixelColor::White,
+        ]);
+        editor.edit_text('\x1b').unwrap();
+        assert_eq!(editor.get_mode(), Mode::Selection);
+        editor.flush();
+        assert_eq!(editor.line(49).trim_end(), "");
+    }
+
+    #[test]
+    fn tile_mode_wraps_around_the_edges() {
+        let tif_image = black(3, 4);
+        let mut editor = headless(tif_image);
+        editor.set_tile(true).unwrap();
+        assert_eq!(editor.canvas_origin(), (3, 4));
+        //the copies show the same pixels
+        assert_eq!(editor.to_image((8, 11)), (2, 3));
+        editor.set_cursor_pos((-1, -1)).unwrap();
+        assert_eq!(editor.cursor.pos, (2, 3));
+        //the short way to the top left corner is across the bottom right one
+        editor.stroke_to((0, 0), PixelColor::Red).unwrap();
+        assert_eq!(editor.cursor.pos, (0, 0));
+        assert_eq!(editor.tif_image.pixels[0][0], PixelColor::Red);
+        assert_eq!(editor.tif_image.pixels[2][3], PixelColor::Red);
+        assert_eq!(editor.tif_image.pixels[1][1], PixelColor::Black);
+        editor.select_all();
+        editor.flush();
+        for copy in [1, 4, 7] {
+            assert_eq!(editor.line(copy)[..12], editor.line(1)[..12]);
+        }
+        assert_ne!(&editor.line(1)[..2], "  ");
+        assert_eq!(&editor.line(1)[1..3], "  ");
+        editor.set_tile(false).unwrap();
+        assert!(editor.set_cursor_pos((-1, 0)).is_err());
+    }
+
+    #[test]
+    fn tiles_need_room() {
+        let tif_image = black(10, 10);
+        let mut editor = headless_sized(tif_image, 30, 60);
+        assert!(editor.set_tile(true).is_err());
+        assert!(!editor.tile);
+        assert!(!editor.too_small);
+    }
+
+    #[test]
+    fn small_terminal_shows_a_warning() {
+        let tif_image = black(10, 10);
+        let editor = headless_sized(tif_image, 10, 40);
+        assert!(editor.too_small);
+        assert!(editor.line(4).contains("TERMINAL TOO SMALL"));
+        assert!(editor.line(5).contains("NEED 25X52, HAVE 10X40"));
+    }
+
+    #[test]
+    fn status_shows_the_whole_cursor_line() {
+        let saved = black(30, 40);
+        let mut tif_image = saved.clone();
+        tif_image.pixels[12][24] = PixelColor::Magenta;
+        let mut editor = headless(tif_image);
+        editor.comparison = Some(Comparison::new(saved, &editor.tif_image));
+        editor.set_cursor_pos((12, 24)).unwrap();
+        editor.redraw_all().unwrap();
+        editor.flush();
+        let status = editor.layout().status;
+        let line: String = editor.line(status.y + 6).chars().take(status.width as usize).collect();
+        assert_eq!(
+            line.trim_end(),
+            "CURSOR: (12, 24) UNDER: Magenta  CHANGED: 1"
+        );
+    }
+
+    #[test]
+    fn help_fits_the_panel() {
+        let mut editor = headless(black(4, 4));
+        for mode in [
+            Mode::Insertion,
+            Mode::Selection,
+            Mode::Area,
+            Mode::Line,
+            Mode::Rectangle,
+            Mode::Remap,
+            Mode::Command,
+            Mode::Paste,
+            Mode::Text,
+        ] {
+            editor.mode = mode;
+            for line in editor.help_lines() {
+                assert!(line.len() <= PANEL_WIDTH as usize, "{:?}: {}", mode, line);
+            }
+        }
+    }
+
+    #[test]
+    fn autosave_keeps_the_warning() {
+        let tif_image = black(10, 10);
+        let mut editor = headless_sized(tif_image.clone(), 10, 40);
+        let path = std::env::temp_dir().join("tif_editor_autosave_small.tif");
+        editor.file = path.to_string_lossy().into_owned();
+        editor.comparison = Some(Comparison::new(tif_image.clone(), &tif_image));
+        //the outline would be drawn on the first row of the canvas
+        editor.select_all();
+        editor.autosave().unwrap();
+        std::fs::remove_file(&path).ok();
+        assert_eq!(editor.line(0).trim(), "");
+        assert!(editor.line(4).contains("TERMINAL TOO SMALL"));
     }
 }
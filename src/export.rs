@@ -0,0 +1,262 @@
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::BufReader;
+use std::io::BufWriter;
+use std::io::Write;
+
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use libtif::image::TifImage;
+
+use crate::pallete::Pallete;
+use crate::pallete::Rgb;
+
+const TIF_HEADER: &[u8] = b".TIF ";
+
+///renders the image with true color escape codes, every pixel takes two columns
+pub fn to_ansi(tif_image: &TifImage, pallete: &Pallete) -> String {
+    let mut out = String::new();
+    for row in &tif_image.pixels {
+        for pixel in row {
+            let rgb = pallete.rgb_of(pixel);
+            out.push_str(&format!("\x1b[48;2;{};{};{}m  ", rgb.0, rgb.1, rgb.2));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+pub fn to_png(tif_image: &TifImage, pallete: &Pallete, path: &str) -> Result<()> {
+    let file = File::create(path)?;
+    let mut encoder = png::Encoder::new(
+        BufWriter::new(file),
+        tif_image.width as u32,
+        tif_image.height as u32,
+    );
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    let data: Vec<u8> = tif_image
+        .pixels
+        .iter()
+        .flatten()
+        .flat_map(|pixel| {
+            let rgb = pallete.rgb_of(pixel);
+            [rgb.0, rgb.1, rgb.2]
+        })
+        .collect();
+    encoder.write_header()?.write_image_data(&data)?;
+    Ok(())
+}
+
+///every pixel becomes the nearest color of the pallete
+pub fn from_png(pallete: &Pallete, path: &str) -> Result<TifImage> {
+    let file = File::open(path).with_context(|| format!("couldnt open {}", path))?;
+    let mut decoder = png::Decoder::new(BufReader::new(file));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size().context("the png is too big")?];
+    let info = reader.next_frame(&mut data)?;
+    let width: u8 = info.width.try_into().map_err(|_| {
+        anyhow!(
+            "{} is {} pixels wide, tif images are at most 255 pixels wide",
+            path,
+            info.width
+        )
+    })?;
+    let samples = info.color_type.samples();
+    let pixels = data[..info.buffer_size()]
+        .chunks(info.line_size)
+        .map(|line| {
+            line.chunks(samples)
+                .take(width as usize)
+                .map(|pixel| {
+                    let rgb = match pixel {
+                        //grayscale, with or without alpha
+                        [v] | [v, _] => Rgb(*v, *v, *v),
+                        [r, g, b, ..] => Rgb(*r, *g, *b),
+                        _ => Rgb(0, 0, 0),
+                    };
+                    pallete.nearest(rgb)
+                })
+                .collect()
+        })
+        .collect();
+    Ok(TifImage {
+        height: info.height as u64,
+        width,
+        pixels,
+    })
+}
+
+///picks the export format from the extension of `path`
+pub fn export(tif_image: &TifImage, pallete: &Pallete, path: &str) -> Result<()> {
+    match extension(path).as_deref() {
+        Some("tif") => save_tif(tif_image, path),
+        Some("png") => to_png(tif_image, pallete, path),
+        Some("ans" | "txt") => {
+            File::create(path)?.write_all(to_ansi(tif_image, pallete).as_bytes())?;
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "cant export to {}: use a .tif, .png, .ans or .txt file",
+            path
+        )),
+    }
+}
+
+///reads a .tif or a .png image and writes it in the format of `output`
+pub fn convert(pallete: &Pallete, input: &str, output: &str) -> Result<()> {
+    let tif_image = match extension(input).as_deref() {
+        Some("png") => from_png(pallete, input)?,
+        _ => open_tif(input)?,
+    };
+    export(&tif_image, pallete, output)
+}
+
+pub fn extension(path: &str) -> Option<String> {
+    path.rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+}
+
+///libtif panics or silently drops pixels on malformed files, so they are checked first
+pub fn open_tif(path: &str) -> Result<TifImage> {
+    let bytes = std::fs::read(path).with_context(|| format!("couldnt read {}", path))?;
+    check_tif(&bytes).with_context(|| format!("{} is not a valid tif file", path))?;
+    TifImage::parse_from_bytes(bytes).map_err(|e| anyhow!("couldnt parse {}: {}", path, e))
+}
+
+///the header, the width byte and the (color, count) runs
+pub fn check_tif(bytes: &[u8]) -> Result<()> {
+    if bytes.len() < TIF_HEADER.len() + 1 || !bytes.starts_with(TIF_HEADER) {
+        return Err(anyhow!("missing the \".TIF \" header"));
+    }
+    let width = bytes[TIF_HEADER.len()] as usize;
+    if width == 0 {
+        return Err(anyhow!("the width is 0"));
+    }
+    let runs = &bytes[TIF_HEADER.len() + 1..];
+    if !runs.len().is_multiple_of(2) {
+        return Err(anyhow!("the last run has no length"));
+    }
+    let pixels: usize = runs.chunks(2).map(|run| run[1] as usize).sum();
+    if !pixels.is_multiple_of(width) {
+        return Err(anyhow!(
+            "{} pixels dont fill rows of {} pixels",
+            pixels,
+            width
+        ));
+    }
+    Ok(())
+}
+
+///writes the image back as a .tif file
+pub fn save_tif(tif_image: &TifImage, path: &str) -> Result<()> {
+    let mut file = OpenOptions::new()
+        .truncate(true)
+        .create(true)
+        .write(true)
+        .open(path)?;
+    file.write_all(&tif_image.save())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libtif::pixel::PixelColor;
+
+    fn fixture(name: &str) -> String {
+        format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name)
+    }
+
+    fn assert_same(a: &TifImage, b: &TifImage) {
+        assert_eq!((a.height, a.width), (b.height, b.width));
+        assert_eq!(a.pixels, b.pixels);
+    }
+
+    ///the fixture parses to `expected`, and saving `expected` gives back the fixture byte by byte
+    fn assert_golden(name: &str, expected: &TifImage) {
+        let parsed = open_tif(&fixture(name)).unwrap();
+        assert_same(&parsed, expected);
+        assert_eq!(expected.save(), std::fs::read(fixture(name)).unwrap());
+    }
+
+    #[test]
+    fn one_pixel() {
+        let expected = TifImage {
+            height: 1,
+            width: 1,
+            pixels: vec![vec![PixelColor::Red]],
+        };
+        assert_golden("red_1x1.tif", &expected);
+    }
+
+    #[test]
+    fn width_255() {
+        let expected = TifImage {
+            height: 73,
+            width: 255,
+            pixels: vec![vec![PixelColor::Black; 255]; 73],
+        };
+        assert_golden("black_73x255.tif", &expected);
+    }
+
+    #[test]
+    fn square() {
+        let expected = TifImage {
+            height: 10,
+            width: 10,
+            pixels: vec![vec![PixelColor::Black; 10]; 10],
+        };
+        assert_golden("black_10x10.tif", &expected);
+    }
+
+    #[test]
+    fn without_pixels() {
+        let expected = TifImage {
+            height: 0,
+            width: 7,
+            pixels: vec![],
+        };
+        assert_golden("empty.tif", &expected);
+    }
+
+    #[test]
+    fn sample_round_trip() {
+        let sample = open_tif(&fixture("sample_60x250.tif")).unwrap();
+        assert_eq!((sample.height, sample.width), (60, 250));
+        let parsed = TifImage::parse_from_bytes(sample.save()).unwrap();
+        assert_same(&parsed, &sample);
+    }
+
+    #[test]
+    fn malformed_files() {
+        assert!(open_tif(&fixture("zero_bytes.tif")).is_err());
+        assert!(open_tif(&fixture("missing.tif")).is_err());
+        assert!(check_tif(b".TIF ").is_err());
+        assert!(check_tif(b".TIFF\x01").is_err());
+        assert!(check_tif(b".TIF \x00").is_err());
+        assert!(check_tif(b".TIF \x02\x5b").is_err());
+        assert!(check_tif(b".TIF \x02\x5b\x03").is_err());
+        assert!(check_tif(b".TIF \x02\x5b\x04").is_ok());
+    }
+
+    #[test]
+    fn png_round_trip() {
+        let pallete = Pallete::new();
+        let tif_image = TifImage {
+            height: 2,
+            width: 3,
+            pixels: vec![
+                vec![PixelColor::Red, PixelColor::Green, PixelColor::Blue],
+                vec![PixelColor::Cyan, PixelColor::Magenta, PixelColor::Yellow],
+            ],
+        };
+        let path = std::env::temp_dir().join("tif_editor_png_round_trip.png");
+        let path = path.to_str().unwrap();
+        to_png(&tif_image, &pallete, path).unwrap();
+        assert_same(&from_png(&pallete, path).unwrap(), &tif_image);
+        std::fs::remove_file(path).ok();
+    }
+}
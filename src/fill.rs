@@ -0,0 +1,41 @@
+use libtif::image::TifImage;
+
+///the region of same colored pixels connected to `start` (up, down, left and right)
+pub fn flood(tif_image: &TifImage, start: (usize, usize)) -> Vec<(usize, usize)> {
+    flood_within(tif_image, start, |_| true)
+}
+
+///like `flood`, but the region never goes through the pixels that arent `inside`
+pub fn flood_within(
+    tif_image: &TifImage,
+    start: (usize, usize),
+    inside: impl Fn((usize, usize)) -> bool,
+) -> Vec<(usize, usize)> {
+    let target = match tif_image.pixels.get(start.0).and_then(|row| row.get(start.1)) {
+        Some(color) => *color,
+        None => return vec![],
+    };
+    let mut visited = vec![vec![false; tif_image.width as usize]; tif_image.pixels.len()];
+    let mut stack = vec![start];
+    let mut region = vec![];
+    while let Some((y, x)) = stack.pop() {
+        if visited[y][x] || tif_image.pixels[y][x] != target || !inside((y, x)) {
+            continue;
+        }
+        visited[y][x] = true;
+        region.push((y, x));
+        if y > 0 {
+            stack.push((y - 1, x));
+        }
+        if y + 1 < tif_image.pixels.len() {
+            stack.push((y + 1, x));
+        }
+        if x > 0 {
+            stack.push((y, x - 1));
+        }
+        if x + 1 < tif_image.pixels[y].len() {
+            stack.push((y, x + 1));
+        }
+    }
+    region
+}
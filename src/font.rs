@@ -0,0 +1,197 @@
+use libtif::pixel::PixelColor;
+
+use crate::stamp::Stamp;
+
+///the built in bitmap fonts of the text tool, only uppercase letters (lowercase ones are drawn as them),
+///digits and some punctuation
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Font {
+    ///3x5
+    Small,
+    ///5x7
+    Large,
+}
+
+impl Font {
+    pub fn next(self) -> Self {
+        match self {
+            Font::Small => Font::Large,
+            Font::Large => Font::Small,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Font::Small => "3x5",
+            Font::Large => "5x7",
+        }
+    }
+
+    ///rows of the glyph of `c`, `#` is drawn. characters without a glyph are drawn as `?`
+    pub fn glyph(self, c: char) -> &'static [&'static str] {
+        let c = c.to_ascii_uppercase();
+        let glyph = match self {
+            Font::Small => SMALL.iter().find(|(glyph, _)| *glyph == c).map(|(_, rows)| &rows[..]),
+            Font::Large => LARGE.iter().find(|(glyph, _)| *glyph == c).map(|(_, rows)| &rows[..]),
+        };
+        glyph.unwrap_or_else(|| self.glyph('?'))
+    }
+
+    ///`text` on one line in `color`, with an empty column between the glyphs. the rest is transparent
+    pub fn render(self, text: &str, color: PixelColor) -> Stamp {
+        let glyphs: Vec<_> = text.chars().map(|c| self.glyph(c)).collect();
+        let height = self.glyph(' ').len();
+        let pixels = (0..height)
+            .map(|y| {
+                let rows: Vec<Vec<_>> = glyphs
+                    .iter()
+                    .map(|glyph| glyph[y].chars().map(|c| (c == '#').then_some(color)).collect())
+                    .collect();
+                rows.join(&None)
+            })
+            .collect();
+        Stamp { pixels }
+    }
+}
+
+const SMALL: &[(char, [&str; 5])] = &[
+    ('A', [".#.", "#.#", "###", "#.#", "#.#"]),
+    ('B', ["##.", "#.#", "##.", "#.#", "##."]),
+    ('C', [".##", "#..", "#..", "#..", ".##"]),
+    ('D', ["##.", "#.#", "#.#", "#.#", "##."]),
+    ('E', ["###", "#..", "##.", "#..", "###"]),
+    ('F', ["###", "#..", "##.", "#..", "#.."]),
+    ('G', [".##", "#..", "#.#", "#.#", ".##"]),
+    ('H', ["#.#", "#.#", "###", "#.#", "#.#"]),
+    ('I', ["###", ".#.", ".#.", ".#.", "###"]),
+    ('J', ["..#", "..#", "..#", "#.#", ".#."]),
+    ('K', ["#.#", "#.#", "##.", "#.#", "#.#"]),
+    ('L', ["#..", "#..", "#..", "#..", "###"]),
+    ('M', ["#.#", "###", "###", "#.#", "#.#"]),
+    ('N', ["##.", "#.#", "#.#", "#.#", "#.#"]),
+    ('O', [".#.", "#.#", "#.#", "#.#", ".#."]),
+    ('P', ["##.", "#.#", "##.", "#..", "#.."]),
+    ('Q', [".#.", "#.#", "#.#", "##.", ".##"]),
+    ('R', ["##.", "#.#", "##.", "#.#", "#.#"]),
+    ('S', [".##", "#..", ".#.", "..#", "##."]),
+    ('T', ["###", ".#.", ".#.", ".#.", ".#."]),
+    ('U', ["#.#", "#.#", "#.#", "#.#", "###"]),
+    ('V', ["#.#", "#.#", "#.#", ".#.", ".#."]),
+    ('W', ["#.#", "#.#", "###", "###", "#.#"]),
+    ('X', ["#.#", "#.#", ".#.", "#.#", "#.#"]),
+    ('Y', ["#.#", "#.#", ".#.", ".#.", ".#."]),
+    ('Z', ["###", "..#", ".#.", "#..", "###"]),
+    ('0', ["###", "#.#", "#.#", "#.#", "###"]),
+    ('1', [".#.", "##.", ".#.", ".#.", "###"]),
+    ('2', ["##.", "..#", ".#.", "#..", "###"]),
+    ('3', ["##.", "..#", ".#.", "..#", "##."]),
+    ('4', ["#.#", "#.#", "###", "..#", "..#"]),
+    ('5', ["###", "#..", "##.", "..#", "##."]),
+    ('6', [".##", "#..", "###", "#.#", "###"]),
+    ('7', ["###", "..#", ".#.", ".#.", ".#."]),
+    ('8', ["###", "#.#", "###", "#.#", "###"]),
+    ('9', ["###", "#.#", "###", "..#", "##."]),
+    (' ', ["...", "...", "...", "...", "..."]),
+    ('.', ["...", "...", "...", "...", ".#."]),
+    (',', ["...", "...", "...", ".#.", "#.."]),
+    ('!', [".#.", ".#.", ".#.", "...", ".#."]),
+    ('?', ["##.", "..#", ".#.", "...", ".#."]),
+    ('-', ["...", "...", "###", "...", "..."]),
+    ('+', ["...", ".#.", "###", ".#.", "..."]),
+    (':', ["...", ".#.", "...", ".#.", "..."]),
+    ('/', ["..#", "..#", ".#.", "#..", "#.."]),
+    ('\'', [".#.", ".#.", "...", "...", "..."]),
+    ('(', ["..#", ".#.", ".#.", ".#.", "..#"]),
+    (')', ["#..", ".#.", ".#.", ".#.", "#.."]),
+    ('=', ["...", "###", "...", "###", "..."]),
+    ('_', ["...", "...", "...", "...", "###"]),
+];
+
+const LARGE: &[(char, [&str; 7])] = &[
+    ('A', [".###.", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('B', ["####.", "#...#", "#...#", "####.", "#...#", "#...#", "####."]),
+    ('C', [".###.", "#...#", "#....", "#....", "#....", "#...#", ".###."]),
+    ('D', ["####.", "#...#", "#...#", "#...#", "#...#", "#...#", "####."]),
+    ('E', ["#####", "#....", "#....", "####.", "#....", "#....", "#####"]),
+    ('F', ["#####", "#....", "#....", "####.", "#....", "#....", "#...."]),
+    ('G', [".###.", "#...#", "#....", "#.###", "#...#", "#...#", ".####"]),
+    ('H', ["#...#", "#...#", "#...#", "#####", "#...#", "#...#", "#...#"]),
+    ('I', [".###.", "..#..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('J', ["..###", "...#.", "...#.", "...#.", "...#.", "#..#.", ".##.."]),
+    ('K', ["#...#", "#..#.", "#.#..", "##...", "#.#..", "#..#.", "#...#"]),
+    ('L', ["#....", "#....", "#....", "#....", "#....", "#....", "#####"]),
+    ('M', ["#...#", "##.##", "#.#.#", "#.#.#", "#...#", "#...#", "#...#"]),
+    ('N', ["#...#", "#...#", "##..#", "#.#.#", "#..##", "#...#", "#...#"]),
+    ('O', [".###.", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('P', ["####.", "#...#", "#...#", "####.", "#....", "#....", "#...."]),
+    ('Q', [".###.", "#...#", "#...#", "#...#", "#.#.#", "#..#.", ".##.#"]),
+    ('R', ["####.", "#...#", "#...#", "####.", "#.#..", "#..#.", "#...#"]),
+    ('S', [".####", "#....", "#....", ".###.", "....#", "....#", "####."]),
+    ('T', ["#####", "..#..", "..#..", "..#..", "..#..", "..#..", "..#.."]),
+    ('U', ["#...#", "#...#", "#...#", "#...#", "#...#", "#...#", ".###."]),
+    ('V', ["#...#", "#...#", "#...#", "#...#", "#...#", ".#.#.", "..#.."]),
+    ('W', ["#...#", "#...#", "#...#", "#.#.#", "#.#.#", "#.#.#", ".#.#."]),
+    ('X', ["#...#", "#...#", ".#.#.", "..#..", ".#.#.", "#...#", "#...#"]),
+    ('Y', ["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#..", "..#.."]),
+    ('Z', ["#####", "....#", "...#.", "..#..", ".#...", "#....", "#####"]),
+    ('0', [".###.", "#...#", "#..##", "#.#.#", "##..#", "#...#", ".###."]),
+    ('1', ["..#..", ".##..", "..#..", "..#..", "..#..", "..#..", ".###."]),
+    ('2', [".###.", "#...#", "....#", "...#.", "..#..", ".#...", "#####"]),
+    ('3', ["#####", "...#.", "..#..", "...#.", "....#", "#...#", ".###."]),
+    ('4', ["...#.", "..##.", ".#.#.", "#..#.", "#####", "...#.", "...#."]),
+    ('5', ["#####", "#....", "####.", "....#", "....#", "#...#", ".###."]),
+    ('6', ["..##.", ".#...", "#....", "####.", "#...#", "#...#", ".###."]),
+    ('7', ["#####", "....#", "...#.", "..#..", ".#...", ".#...", ".#..."]),
+    ('8', [".###.", "#...#", "#...#", ".###.", "#...#", "#...#", ".###."]),
+    ('9', [".###.", "#...#", "#...#", ".####", "....#", "...#.", ".##.."]),
+    (' ', [".....", ".....", ".....", ".....", ".....", ".....", "....."]),
+    ('.', [".....", ".....", ".....", ".....", ".....", ".##..", ".##.."]),
+    (',', [".....", ".....", ".....", ".....", ".##..", "..#..", ".#..."]),
+    ('!', ["..#..", "..#..", "..#..", "..#..", "..#..", ".....", "..#.."]),
+    ('?', [".###.", "#...#", "....#", "...#.", "..#..", ".....", "..#.."]),
+    ('-', [".....", ".....", ".....", "#####", ".....", ".....", "....."]),
+    ('+', [".....", "..#..", "..#..", "#####", "..#..", "..#..", "....."]),
+    (':', [".....", ".##..", ".##..", ".....", ".##..", ".##..", "....."]),
+    ('/', ["....#", "....#", "...#.", "..#..", ".#...", "#....", "#...."]),
+    ('\'', ["..#..", "..#..", ".#...", ".....", ".....", ".....", "....."]),
+    ('(', ["...#.", "..#..", ".#...", ".#...", ".#...", "..#..", "...#."]),
+    (')', [".#...", "..#..", "...#.", "...#.", "...#.", "..#..", ".#..."]),
+    ('=', [".....", ".....", "#####", ".....", "#####", ".....", "....."]),
+    ('_', [".....", ".....", ".....", ".....", ".....", ".....", "#####"]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glyphs_have_the_size_of_their_font() {
+        for (font, width) in [(Font::Small, 3), (Font::Large, 5)] {
+            let glyphs: Vec<char> = match font {
+                Font::Small => SMALL.iter().map(|(c, _)| *c).collect(),
+                Font::Large => LARGE.iter().map(|(c, _)| *c).collect(),
+            };
+            for c in glyphs {
+                for row in font.glyph(c) {
+                    assert_eq!(row.len(), width, "{:?} of {}", c, font.name());
+                    assert!(row.chars().all(|pixel| matches!(pixel, '#' | '.')));
+                }
+            }
+        }
+        assert_eq!(SMALL.len(), LARGE.len());
+    }
+
+    #[test]
+    fn text_is_drawn_left_to_right() {
+        let stamp = Font::Small.render("hi", PixelColor::Red);
+        let rows: Vec<String> = stamp
+            .pixels
+            .iter()
+            .map(|row| row.iter().map(|pixel| if pixel.is_some() { '#' } else { '.' }).collect())
+            .collect();
+        assert_eq!(rows, ["#.#.###", "#.#..#.", "###..#.", "#.#..#.", "#.#.###"]);
+        assert_eq!(stamp.pixels_at((0, 0))[0], ((0, 0), PixelColor::Red));
+        assert_eq!(Font::Large.glyph('~'), Font::Large.glyph('?'));
+        assert!(Font::Large.render("", PixelColor::Red).pixels_at((0, 0)).is_empty());
+    }
+}
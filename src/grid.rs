@@ -0,0 +1,59 @@
+pub const GRID_SIZES: [u8; 4] = [2, 4, 8, 16];
+
+///rows taken by the column ruler above the canvas
+pub const TOP_RULER_HEIGHT: i32 = 2;
+
+///dotted lines every `size` pixels, drawn over the pixels without changing them
+pub struct Grid {
+    pub visible: bool,
+    pub size: u8,
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        Self {
+            visible: false,
+            size: 8,
+        }
+    }
+
+    pub fn next_size(&self) -> u8 {
+        let i = GRID_SIZES.iter().position(|s| *s == self.size).unwrap_or(0);
+        GRID_SIZES[(i + 1) % GRID_SIZES.len()]
+    }
+
+    ///the character drawn over the pixel at `pos`, `None` when no grid line goes through it
+    pub fn char_at(&self, pos: (usize, usize)) -> Option<char> {
+        if !self.visible {
+            return None;
+        }
+        let size = self.size as usize;
+        match (pos.0.is_multiple_of(size), pos.1.is_multiple_of(size)) {
+            (true, true) => Some('+'),
+            (true, false) | (false, true) => Some('.'),
+            (false, false) => None,
+        }
+    }
+}
+
+///columns taken by the row ruler on the left of the canvas, numbers plus a space
+pub fn left_ruler_width(height: u64) -> i32 {
+    height.saturating_sub(1).to_string().len() as i32 + 1
+}
+
+///the two lines of the column ruler: the index of every 10th column and the last digit of every column
+pub fn top_ruler(width: u8) -> (String, String) {
+    let mut labels = String::new();
+    let mut digits = String::new();
+    for x in 0..width as usize {
+        if x % 10 == 0 {
+            labels.truncate(x);
+            labels.push_str(&x.to_string());
+        } else if labels.len() <= x {
+            labels.push(' ');
+        }
+        digits.push(char::from_digit((x % 10) as u32, 10).unwrap_or(' '));
+    }
+    labels.truncate(width as usize);
+    (labels, digits)
+}
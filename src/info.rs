@@ -0,0 +1,53 @@
+use std::fmt::Write;
+
+use anyhow::Context;
+use anyhow::Result;
+use libtif::image::TifImage;
+
+use crate::export;
+use crate::pallete::Pallete;
+
+///number of pixels of every color, indexed by pallete slot
+pub fn histogram(tif_image: &TifImage, pallete: &Pallete) -> Vec<usize> {
+    let mut counts = vec![0; pallete.colors.len()];
+    for pixel in tif_image.pixels.iter().flatten() {
+        counts[pallete.slot_of(pixel)] += 1;
+    }
+    counts
+}
+
+///the report printed by the `info` subcommand
+pub fn info(pallete: &Pallete, path: &str) -> Result<String> {
+    let tif_image = export::open_tif(path)?;
+    let bytes = std::fs::metadata(path)
+        .with_context(|| format!("couldnt read {}", path))?
+        .len();
+    let total = tif_image.height * tif_image.width as u64;
+    let mut out = String::new();
+    writeln!(out, "file: {}", path)?;
+    writeln!(
+        out,
+        "format: run length encoded tif, {} bytes, runs: {}",
+        bytes,
+        //the header and the width byte, then two bytes per run
+        bytes.saturating_sub(6) / 2
+    )?;
+    writeln!(
+        out,
+        "size: {}x{} (height x width), {} pixels",
+        tif_image.height, tif_image.width, total
+    )?;
+    writeln!(out, "colors:")?;
+    for (slot, count) in histogram(&tif_image, pallete).iter().enumerate() {
+        let color = format!("{:?}", pallete.colors[slot]);
+        writeln!(
+            out,
+            "  [{}] {:<8} {:>6} {:>6.1}%",
+            slot + 1,
+            color,
+            count,
+            *count as f64 * 100.0 / total.max(1) as f64
+        )?;
+    }
+    Ok(out)
+}
@@ -0,0 +1,172 @@
+use crate::grid;
+
+///columns of the help and statistics panel
+pub const PANEL_WIDTH: i32 = 42;
+///mode, both colors, mirror, brush, pattern and grid, then the cursor
+pub const STATUS_HEIGHT: i32 = 7;
+///the status is as wide as the left column, but never narrower than this
+pub const STATUS_WIDTH: i32 = 40;
+///the markers, the swatches, the digits (next to the second row of the pattern previews)
+///and the two rows of the remap preview
+pub const PALETTE_HEIGHT: i32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rect {
+    pub y: i32,
+    pub x: i32,
+    pub height: i32,
+    pub width: i32,
+}
+
+impl Rect {
+    pub fn new(y: i32, x: i32, height: i32, width: i32) -> Self {
+        Self {
+            y,
+            x,
+            height,
+            width,
+        }
+    }
+
+    ///first row below the rect
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height
+    }
+
+    ///first column on the right of the rect
+    pub fn right(&self) -> i32 {
+        self.x + self.width
+    }
+
+    pub fn contains(&self, pos: (i32, i32)) -> bool {
+        (self.y..self.bottom()).contains(&pos.0) && (self.x..self.right()).contains(&pos.1)
+    }
+
+    #[cfg(test)]
+    pub fn overlaps(&self, other: &Rect) -> bool {
+        self.y < other.bottom()
+            && other.y < self.bottom()
+            && self.x < other.right()
+            && other.x < self.right()
+    }
+}
+
+///where every part of the editor goes: the canvas, the palette and the status are stacked
+///on the left, the help (or statistics) panel is on their right and the message line is the last row
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Layout {
+    ///(height, width) of the terminal
+    pub term: (i32, i32),
+    ///the rulers, when shown, take the rows above and the columns on the left of it
+    pub canvas: Rect,
+    ///the canvas and, in tile mode, the 8 copies of the image around it
+    pub tiles: Rect,
+    ///the tiles and their border, one row below and one column on the right
+    pub framed_canvas: Rect,
+    pub palette: Rect,
+    pub status: Rect,
+    ///`None` when the terminal is too narrow for it, the editor works without it
+    pub panel: Option<Rect>,
+    pub message: Rect,
+}
+
+impl Layout {
+    ///`palette_width` is the width of the swatches and the pattern previews
+    pub fn new(term: (i32, i32), image: (u64, u8), rulers: bool, tile: bool, palette_width: i32) -> Self {
+        let (top, left) = if rulers {
+            (grid::TOP_RULER_HEIGHT, grid::left_ruler_width(image.0))
+        } else {
+            (0, 0)
+        };
+        let (height, width) = (image.0 as i32, image.1 as i32);
+        //copies of the image on every side of the canvas
+        let copies = if tile { 1 } else { 0 };
+        let tiles = Rect::new(top, left, height * (1 + 2 * copies), width * (1 + 2 * copies));
+        let canvas = Rect::new(top + height * copies, left + width * copies, height, width);
+        let framed_canvas = Rect::new(0, 0, tiles.bottom() + 1, tiles.right() + 1);
+        //an empty row between the border and the palette
+        let palette = Rect::new(framed_canvas.bottom() + 1, 0, PALETTE_HEIGHT, palette_width);
+        let left_width = framed_canvas.width.max(palette.width).max(STATUS_WIDTH);
+        let status = Rect::new(palette.bottom(), 0, STATUS_HEIGHT, left_width);
+        let panel_x = left_width + 2;
+        let panel = (panel_x + PANEL_WIDTH <= term.1)
+            .then(|| Rect::new(0, panel_x, (term.0 - 1).max(0), PANEL_WIDTH));
+        let message = Rect::new(
+            (term.0 - 1).max(status.bottom()),
+            0,
+            1,
+            term.1.max(left_width),
+        );
+        Self {
+            term,
+            canvas,
+            tiles,
+            framed_canvas,
+            palette,
+            status,
+            panel,
+            message,
+        }
+    }
+
+    ///(height, width) of the smallest terminal that fits everything but the panel
+    pub fn required_size(&self) -> (i32, i32) {
+        let width = self
+            .framed_canvas
+            .right()
+            .max(self.palette.right())
+            .max(self.status.right());
+        (self.status.bottom() + 1, width)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn regions_never_overlap(
+            spare in (0..50i32, 0..150i32),
+            image in (1..150u64, 1..=255u8),
+            rulers in any::<bool>(),
+            tile in any::<bool>(),
+        ) {
+            //only terminals that fit the editor, the tiles rarely fit a random one
+            let required = Layout::new((0, 0), image, rulers, tile, 52).required_size();
+            let term = (required.0 + spare.0, required.1 + spare.1);
+            let layout = Layout::new(term, image, rulers, tile, 52);
+            let mut regions = vec![layout.framed_canvas, layout.palette, layout.status, layout.message];
+            regions.extend(layout.panel);
+            for (i, a) in regions.iter().enumerate() {
+                for b in &regions[i + 1..] {
+                    prop_assert!(!a.overlaps(b), "{:?} overlaps {:?}", a, b);
+                }
+            }
+            for region in &regions {
+                prop_assert!(region.bottom() <= term.0 && region.right() <= term.1);
+            }
+            prop_assert!(layout.tiles.contains((layout.canvas.y, layout.canvas.x)));
+            prop_assert!(layout.tiles.contains((layout.canvas.bottom() - 1, layout.canvas.right() - 1)));
+        }
+    }
+
+    #[test]
+    fn panel_needs_room() {
+        let layout = Layout::new((40, 80), (10, 10), false, false, 52);
+        assert_eq!(layout.required_size(), (25, 52));
+        assert_eq!(layout.panel, None);
+        let layout = Layout::new((40, 120), (10, 10), false, false, 52);
+        assert_eq!(layout.panel, Some(Rect::new(0, 54, 39, PANEL_WIDTH)));
+        assert_eq!(layout.message, Rect::new(39, 0, 1, 120));
+    }
+
+    #[test]
+    fn canvas_is_the_middle_tile() {
+        let layout = Layout::new((60, 120), (10, 20), false, true, 52);
+        assert_eq!(layout.tiles, Rect::new(0, 0, 30, 60));
+        assert_eq!(layout.canvas, Rect::new(10, 20, 10, 20));
+        assert_eq!(layout.required_size(), (45, 61));
+    }
+}
@@ -1,59 +1,335 @@
-use std::{fs::OpenOptions, io::Write};
+use std::io::{Read, Write};
+use std::time::{Duration, Instant};
 
-use anyhow::Result;
-use clap::Parser;
-use color::Color;
+use anyhow::{anyhow, Context, Result};
+use brush::Brush;
+use clap::{Parser, Subcommand};
 use editor::Editor;
+use grid::Grid;
 use libtif::{image::TifImage, pixel::PixelColor};
 use mode::Mode;
-use pancurses::{endwin, getmouse, mousemask, Input, ALL_MOUSE_EVENTS, REPORT_MOUSE_POSITION};
+use pallete::Pallete;
+use selection::Combine;
+use session::{FileState, Session};
+use timer::Timer;
+use mouse::MouseState;
+use pancurses::{
+    can_change_color, endwin, getmouse, mouseinterval, mousemask, Input, ALL_MOUSE_EVENTS,
+    REPORT_MOUSE_POSITION,
+};
 
 mod area;
+mod brush;
 mod color;
+mod command;
 mod cursor;
+mod diff;
 mod editor;
+mod fill;
+mod font;
+mod grid;
+mod export;
+mod info;
+mod layout;
 mod mode;
+mod mouse;
 mod pallete;
+mod pattern;
+mod recolor;
+mod screen;
+mod selection;
+mod session;
+mod shapes;
+mod stamp;
+mod stats;
+mod symmetry;
+mod timer;
 
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
-    #[clap(short, long, value_parser)]
-    file: String,
+    ///file with the rgb value of every color, one `<color> #rrggbb` per line
+    #[clap(short, long, value_parser, global = true)]
+    palette: Option<String>,
+
+    #[clap(subcommand)]
+    command: Commands,
+}
+
+///how the editor behaves while it is open
+#[derive(clap::Args, Debug)]
+struct EditorOptions {
+    ///save the image every this many seconds while it has unsaved changes
+    #[clap(long, value_parser = clap::value_parser!(u64).range(1..))]
+    autosave: Option<u64>,
+
+    ///blink the cursor, so the color under it can be seen
+    #[clap(long, value_parser)]
+    blink: bool,
+
+    ///directory of the .tif files of the stamp library
+    #[clap(long, value_parser, default_value = "stamps")]
+    stamps: String,
+
+    ///file with the recently opened files and where every one of them was left.
+    ///the default one is in the current directory, every directory has its own recent files
+    #[clap(long, value_parser, default_value = session::DEFAULT_PATH)]
+    session: String,
+}
+
+#[derive(Subcommand, Debug)]
+enum Commands {
+    ///open an image in the editor, where it was left the last time
+    Edit {
+        #[clap(short, long, value_parser)]
+        file: String,
+
+        ///run the editor commands of this file (`-` for stdin) instead of opening the editor,
+        ///the image is saved once every command ran
+        #[clap(short, long, value_parser)]
+        script: Option<String>,
+
+        #[clap(flatten)]
+        options: EditorOptions,
+    },
+    ///create an image and open it in the editor
+    New {
+        #[clap(short, long, value_parser)]
+        file: String,
+
+        ///at most 10000, like `resize` in scripts
+        #[clap(long, value_parser = clap::value_parser!(u64).range(1..=command::MAX_HEIGHT))]
+        height: u64,
+
+        ///at most 255, the width is stored in a single byte
+        #[clap(long, value_parser = clap::value_parser!(u8).range(1..))]
+        width: u8,
 
-    #[clap(short, long, value_parser)]
-    create: bool,
+        ///color of every pixel of the new image
+        #[clap(short, long, value_parser = parse_color, default_value = "black")]
+        color: PixelColor,
 
-    #[clap(short, long, value_parser, default_value_t = 1)]
-    height: i32,
+        ///overwrite the file if it already exists
+        #[clap(long, value_parser)]
+        force: bool,
 
-    #[clap(short, long, value_parser, default_value_t = 1)]
-    width: u8,
+        ///only write the file, without opening the editor
+        #[clap(long, value_parser)]
+        no_edit: bool,
+
+        #[clap(flatten)]
+        options: EditorOptions,
+    },
+    ///print the size, the colors and the format details of an image
+    Info {
+        #[clap(value_parser)]
+        file: String,
+    },
+    ///convert an image to another format
+    ///
+    ///the formats are picked from the extensions:
+    ///.tif and .png (every pixel becomes the nearest color) can be read,
+    ///.tif, .png, .ans and .txt (true color escape codes) can be written
+    Convert {
+        #[clap(value_parser)]
+        input: String,
+
+        #[clap(value_parser)]
+        output: String,
+    },
+    ///compare two images, exits with 1 when they are different
+    Diff {
+        #[clap(value_parser)]
+        old: String,
+
+        #[clap(value_parser)]
+        new: String,
+
+        #[clap(long, value_enum, default_value = "side-by-side")]
+        view: diff::View,
+    },
+    ///print the recently opened files, the last one first
+    Recent {
+        ///the session of `edit`, the default one is in the current directory
+        #[clap(long, value_parser, default_value = session::DEFAULT_PATH)]
+        session: String,
+    },
+}
+
+fn parse_color(name: &str) -> Result<PixelColor, String> {
+    pallete::parse_color_name(name).ok_or_else(|| format!("unknown color {:?}", name))
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
 
-    let tif = if !args.create {
-        TifImage::parse_from_file(args.file.to_owned()).expect("Couldnt parse file!")
-    } else {
-        TifImage {
-            height: args.height as u64,
-            width: args.width as u8,
-            pixels: vec![vec![PixelColor::Black; args.width as usize]; args.height as usize],
+    let pallete = match &args.palette {
+        Some(path) => Pallete::from_file(path)?,
+        None => Pallete::new(),
+    };
+    match args.command {
+        Commands::Edit {
+            file,
+            script,
+            options,
+        } => {
+            let mut tif = export::open_tif(&file)?;
+            match script {
+                Some(path) => {
+                    let mut script = String::new();
+                    if path == "-" {
+                        std::io::stdin().read_to_string(&mut script)?;
+                    } else {
+                        script = std::fs::read_to_string(&path)
+                            .with_context(|| format!("couldnt read the script {}", path))?;
+                    }
+                    command::run_script(&script, &mut tif, &file)?;
+                    export::save_tif(&tif, &file)
+                }
+                None => edit(tif, args.palette, file, options, true),
+            }
+        }
+        Commands::New {
+            file,
+            height,
+            width,
+            color,
+            force,
+            no_edit,
+            options,
+        } => {
+            if !force && std::path::Path::new(&file).exists() {
+                return Err(anyhow!(
+                    "{} already exists, use --force to overwrite it or `edit` to open it",
+                    file
+                ));
+            }
+            let tif = TifImage {
+                height,
+                width,
+                pixels: vec![vec![color; width as usize]; height as usize],
+            };
+            export::save_tif(&tif, &file)?;
+            if no_edit {
+                return Ok(());
+            }
+            edit(tif, args.palette, file, options, false)
         }
+        Commands::Info { file } => {
+            print!("{}", info::info(&pallete, &file)?);
+            Ok(())
+        }
+        Commands::Convert { input, output } => export::convert(&pallete, &input, &output),
+        Commands::Diff { old, new, view } => {
+            let old = export::open_tif(&old)?;
+            let new = export::open_tif(&new)?;
+            print!("{}", diff::render(&old, &new, &pallete, view));
+            print!("{}", diff::report(&old, &new));
+            if !diff::changed_pixels(&old, &new).is_empty() {
+                std::process::exit(1);
+            }
+            Ok(())
+        }
+        Commands::Recent { session } => {
+            for file in Session::load(&session)?.recent() {
+                println!("{}", file);
+            }
+            Ok(())
+        }
+    }
+}
+
+///runs the editor until it is closed, then saves the image to `file` and remembers how it was left.
+///with `restore`, the cursor, the selected color and the palette (without `--palette`)
+///are the ones it was left with the last time
+fn edit(
+    tif: TifImage,
+    palette: Option<String>,
+    file: String,
+    options: EditorOptions,
+    restore: bool,
+) -> Result<()> {
+    //a session that cant be read isnt overwritten
+    let mut session = Session::load(&options.session);
+    let key = session::absolute(&file);
+    let restored = match &session {
+        Ok(session) if restore => session.get(&key).cloned(),
+        _ => None,
+    };
+    let mut palette = palette.map(|path| session::absolute(&path));
+    let mut pallete = match &palette {
+        Some(path) => Pallete::from_file(path)?,
+        None => Pallete::new(),
     };
-    let mut editor = Editor::new(tif);
-    editor.is_terminal_size_enough()?;
-    editor.draw_ui()?;
-    editor.draw_help().ok(); //dont handle this error
+    //a remembered palette that cant be read anymore is left out
+    let mut lost_palette = None;
+    if let Some(path) = restored.as_ref().and_then(|restored| restored.palette.clone()) {
+        if palette.is_none() {
+            match Pallete::from_file(&path) {
+                Ok(remembered) => {
+                    pallete = remembered;
+                    palette = Some(path);
+                }
+                Err(e) => lost_palette = Some(e),
+            }
+        }
+    }
+    //the image as it was last written to the file
+    let mut saved = tif.clone();
+    let mut editor = Editor::new(tif, pallete, file);
+    editor.stamps_dir = options.stamps;
+    if let Some(restored) = &restored {
+        //the image might be smaller now
+        editor.set_cursor_pos(restored.cursor).ok();
+        editor.selected_color = restored.selected_color;
+    }
+    editor.redraw_all()?;
     if mousemask(ALL_MOUSE_EVENTS | REPORT_MOUSE_POSITION, None) == 0 {
-        editor.mvprintw(40, 0, "COULD NOT GET MOUSE EVENTS!");
-        editor.refresh();
+        editor.draw_message("COULD NOT GET MOUSE EVENTS!");
+    }
+    //report presses and releases right away instead of merging them into clicks,
+    //and ask the terminal to report the mouse position while a button is held
+    mouseinterval(0);
+    print!("\x1b[?1002h");
+    std::io::stdout().flush()?;
+    let mut mouse = MouseState::new();
+    if let Some(e) = session.as_ref().err().or(lost_palette.as_ref()) {
+        editor.draw_message(&format!("ERROR: {:#}", e));
+    }
+    if palette.is_some() && !can_change_color() {
+        editor.draw_message("THIS TERMINAL CANT CHANGE COLORS, THE PALETTE IS ONLY USED IN EXPORTS!");
     }
+    editor.flush();
+    let mut autosave = options.autosave.map(|secs| Timer::new(Duration::from_secs(secs)));
+    let mut blink = options.blink.then(|| Timer::new(Duration::from_millis(500)));
+    let mut march = Timer::new(Duration::from_millis(300));
 
     'editor: loop {
+        //sleeps until there is input or a timer is due
+        //the outline only marches while there is a selection
+        let marching = editor.selection.as_ref().map(|_| &march);
+        editor.timeout(timer::wait_time(
+            autosave.iter().chain(blink.iter()).chain(marching),
+            Instant::now(),
+        ));
         if let Some(c) = editor.getch() {
+            //the cursor stays visible while it is being used
+            if let Some(blink) = blink.as_mut() {
+                editor.blink(false);
+                blink.restart(Instant::now());
+            }
+            if c == Input::KeyResize {
+                editor.resize();
+                editor.redraw_all()?;
+                editor.flush();
+                continue;
+            }
+            if editor.too_small {
+                if c == Input::Character('q') {
+                    break 'editor;
+                }
+                continue;
+            }
             let mut cursor_pos = editor.cursor.pos;
             match c {
                 Input::KeyLeft => {
@@ -72,23 +348,145 @@ fn main() -> Result<()> {
                     cursor_pos.0 += 1;
                     editor.set_cursor_pos(cursor_pos).ok();
                 }
+                Input::KeyBackspace if editor.get_mode() == Mode::Command => {
+                    editor.edit_command_line('\x7f')?;
+                }
+                Input::KeyBackspace if editor.get_mode() == Mode::Text => {
+                    editor.edit_text('\x7f')?;
+                }
+                Input::KeyEnter if editor.get_mode() == Mode::Text => {
+                    editor.edit_text('\n')?;
+                }
+                Input::KeyEnter if editor.get_mode() == Mode::Command => {
+                    editor.edit_command_line('\n')?;
+                }
+                Input::KeyEnter if editor.get_mode() == Mode::Area => {
+                    editor.set_mode(Mode::Selection);
+                }
+                Input::KeyEnter if editor.get_mode() == Mode::Paste => {
+                    editor.stamp()?;
+                }
+                Input::KeyDC if matches!(editor.get_mode(), Mode::Selection | Mode::Area) => {
+                    if let Err(e) = editor.delete_selection() {
+                        editor.draw_message(&format!("ERROR: {:#}", e));
+                    }
+                    editor.set_mode(Mode::Selection);
+                }
                 Input::Character(c) => {
-                    if c == '\x1b' {
+                    let mode_before = editor.get_mode();
+                    if editor.get_mode() == Mode::Command {
+                        editor.edit_command_line(c)?;
+                    } else if editor.get_mode() == Mode::Text {
+                        editor.edit_text(c)?;
+                    } else if c == '\x1b' {
                         editor.set_mode(Mode::Selection);
                     } else if c == 'i' && editor.get_mode() == Mode::Selection {
                         editor.set_mode(Mode::Insertion);
                     } else if c == 'q' && editor.get_mode() == Mode::Selection {
                         break 'editor;
-                    } else if matches!(c, '0'..='8') && editor.get_mode() == Mode::Selection {
-                        let color = c as u8 - 48;
-                        editor.set_selected_color(Color(color as u32).into());
+                    } else if matches!(c, '1'..='9') && editor.get_mode() == Mode::Selection {
+                        let slot = c as usize - '1' as usize;
+                        if let Some(color) = editor.pallete.color_at(slot) {
+                            editor.set_selected_color(color);
+                        }
+                    } else if matches!(c, '1'..='9') && editor.get_mode() == Mode::Remap {
+                        let slot = c as usize - '1' as usize;
+                        if let Some(color) = editor.pallete.color_at(slot) {
+                            editor.set_remap_target(color);
+                        }
                     } else if c == ' ' && editor.get_mode() == Mode::Insertion {
                         editor.set_pix_at_cursor(editor.selected_color)?;
                     } else if c == 's' && editor.get_mode() == Mode::Selection {
                         editor.area_mode();
+                    } else if c == 'x' {
+                        editor.swap_colors();
+                    } else if c == 'e' && editor.get_mode() != Mode::Area {
+                        editor.pick_color_at_cursor();
+                    } else if c == 'l' && editor.get_mode() == Mode::Selection {
+                        editor.shape_mode(Mode::Line);
+                    } else if c == 'r' && editor.get_mode() == Mode::Selection {
+                        editor.shape_mode(Mode::Rectangle);
+                    } else if matches!(c, '+' | '=')
+                        && matches!(editor.get_mode(), Mode::Selection | Mode::Insertion)
+                    {
+                        editor.set_brush(editor.cursor.brush.bigger());
+                    } else if c == '-'
+                        && matches!(editor.get_mode(), Mode::Selection | Mode::Insertion)
+                    {
+                        editor.set_brush(editor.cursor.brush.smaller());
+                    } else if c == 'b'
+                        && matches!(editor.get_mode(), Mode::Selection | Mode::Insertion)
+                    {
+                        let brush = editor.cursor.brush;
+                        editor.set_brush(Brush {
+                            shape: brush.shape.next(),
+                            ..brush
+                        });
+                    } else if c == 'p'
+                        && matches!(editor.get_mode(), Mode::Selection | Mode::Insertion)
+                    {
+                        editor.set_pattern(editor.pattern.next());
+                    } else if c == 'f'
+                        && matches!(editor.get_mode(), Mode::Selection | Mode::Insertion)
+                    {
+                        editor.bucket_fill(editor.selected_color)?;
+                    } else if c == 'c'
+                        && matches!(
+                            editor.get_mode(),
+                            Mode::Selection | Mode::Insertion | Mode::Area
+                        )
+                    {
+                        editor.replace_color_at_cursor(editor.selected_color)?;
+                    } else if c == 'C' && editor.get_mode() == Mode::Selection {
+                        editor.remap_mode();
+                    } else if c == 'g' && editor.get_mode() == Mode::Selection {
+                        editor.set_grid(Grid {
+                            visible: !editor.grid.visible,
+                            size: editor.grid.size,
+                        });
+                    } else if c == 'G' && editor.get_mode() == Mode::Selection {
+                        editor.set_grid(Grid {
+                            visible: true,
+                            size: editor.grid.next_size(),
+                        });
+                    } else if c == 'R' && editor.get_mode() == Mode::Selection {
+                        editor.set_rulers(!editor.show_rulers)?;
+                    } else if c == 'm' && editor.get_mode() == Mode::Selection {
+                        editor.set_symmetry(editor.symmetry.next());
+                    } else if c == 'T' && editor.get_mode() == Mode::Selection {
+                        editor.text_mode();
+                    } else if c == 't' && editor.get_mode() == Mode::Selection {
+                        if let Err(e) = editor.set_tile(!editor.tile) {
+                            editor.draw_message(&format!("ERROR: {:#}", e));
+                        }
+                    } else if c == ':' && editor.get_mode() == Mode::Selection {
+                        editor.command_mode();
+                    } else if c == 'D' && editor.get_mode() == Mode::Selection {
+                        editor.toggle_comparison();
+                    } else if c == '\t' {
+                        editor.set_show_stats(!editor.show_stats);
+                    } else if c == 'a' && editor.get_mode() == Mode::Selection {
+                        editor.select_all();
+                    } else if c == 'n' && editor.get_mode() == Mode::Selection {
+                        editor.select_none();
+                    } else if c == 'v' && editor.get_mode() == Mode::Selection {
+                        editor.invert_selection();
+                    } else if matches!(c, '\x03' | '\x16' | 'o') && editor.get_mode() == Mode::Selection {
+                        let result = match c {
+                            //ctrl + c and ctrl + v
+                            '\x03' => editor.copy_selection(),
+                            '\x16' => editor.paste_clipboard(),
+                            _ => editor.open_library(),
+                        };
+                        if let Err(e) = result {
+                            editor.draw_message(&format!("ERROR: {:#}", e));
+                        }
                     }
 
-                    if editor.get_mode() == Mode::Insertion {
+                    if editor.get_mode() != mode_before {
+                        //the key that entered a mode isnt used by that mode too,
+                        //`s` would move the anchor of Area mode down a row right away
+                    } else if editor.get_mode() == Mode::Insertion {
                         match c.to_ascii_lowercase() {
                             'a' => {
                                 cursor_pos.1 -= 1;
@@ -118,75 +516,137 @@ fn main() -> Result<()> {
                                 cursor_pos.1 -= 1;
                                 editor.set_cursor_pos(cursor_pos).ok(); //using the result of this function might end up in unncessary crashes
                                 editor.set_area_based_on_current_cursor_position()?;
-                                editor.draw_area()?;
                             }
                             'd' => {
                                 cursor_pos.1 += 1;
                                 editor.set_cursor_pos(cursor_pos).ok();
                                 editor.set_area_based_on_current_cursor_position()?;
-                                editor.draw_area()?;
                             }
                             'w' => {
                                 cursor_pos.0 -= 1;
                                 editor.set_cursor_pos(cursor_pos).ok();
                                 editor.set_area_based_on_current_cursor_position()?;
-                                editor.draw_area()?;
                             }
                             's' => {
                                 cursor_pos.0 += 1;
                                 editor.set_cursor_pos(cursor_pos).ok();
                                 editor.set_area_based_on_current_cursor_position()?;
-                                editor.draw_area()?;
                             }
                             ' ' => {
                                 editor.set_area_color(editor.selected_color)?;
                                 editor.set_mode(Mode::Selection);
                             }
+                            '\n' | '\r' => editor.set_mode(Mode::Selection),
+                            't' => editor.set_selection_tool(editor.selection_tool.next()),
+                            '+' | '=' => editor.toggle_combine(Combine::Add),
+                            '-' => editor.toggle_combine(Combine::Subtract),
                             _ => {}
                         }
-                    }
-                }
-                Input::KeyMouse => {
-                    match getmouse() {
-                        Ok(mouse) => match mouse.bstate {
-                            4 if editor.get_mode() == Mode::Area => {
-                                editor.set_cursor_pos((mouse.y, mouse.x)).ok();
-                                editor.set_area_based_on_current_cursor_position()?;
-                                editor.draw_area_pixels()?;
-                                editor.draw_area()?;
+                    } else if editor.get_mode() == Mode::Paste {
+                        match c.to_ascii_lowercase() {
+                            'a' => cursor_pos.1 -= 1,
+                            'd' => cursor_pos.1 += 1,
+                            'w' => cursor_pos.0 -= 1,
+                            's' => cursor_pos.0 += 1,
+                            ' ' | '\n' | '\r' => editor.stamp()?,
+                            '[' | ']' => {
+                                if let Err(e) = editor.select_stamp(if c == '[' { -1 } else { 1 }) {
+                                    editor.draw_message(&format!("ERROR: {:#}", e));
+                                }
                             }
-                            8 if editor.get_mode() == Mode::Area => {
-                                editor.set_area_color(editor.selected_color)?;
-                                editor.set_mode(Mode::Selection);
-                                editor.set_cursor_pos((mouse.y, mouse.x)).ok();
-                                editor.refresh();
+                            _ => {}
+                        }
+                        editor.set_cursor_pos(cursor_pos).ok();
+                    } else if editor.get_mode() == Mode::Remap {
+                        match c.to_ascii_lowercase() {
+                            'a' => editor.move_remap_slot(-1),
+                            'd' => editor.move_remap_slot(1),
+                            ' ' | '\n' => editor.apply_remap(),
+                            _ => {}
+                        }
+                    } else if editor.get_mode().shape().is_some() {
+                        match c.to_ascii_lowercase() {
+                            'a' => {
+                                cursor_pos.1 -= 1;
+                                editor.set_cursor_pos(cursor_pos).ok();
+                                editor.set_shape_end(editor.cursor.pos)?;
                             }
-                            4 => {
-                                editor.set_cursor_pos((mouse.y, mouse.x)).ok();
+                            'd' => {
+                                cursor_pos.1 += 1;
+                                editor.set_cursor_pos(cursor_pos).ok();
+                                editor.set_shape_end(editor.cursor.pos)?;
                             }
-                            8 if editor.get_mode() == Mode::Insertion => {
-                                editor.set_cursor_pos((mouse.y, mouse.x)).ok();
-                                editor.set_pix_at_cursor(editor.selected_color)?;
+                            'w' => {
+                                cursor_pos.0 -= 1;
+                                editor.set_cursor_pos(cursor_pos).ok();
+                                editor.set_shape_end(editor.cursor.pos)?;
+                            }
+                            's' => {
+                                cursor_pos.0 += 1;
+                                editor.set_cursor_pos(cursor_pos).ok();
+                                editor.set_shape_end(editor.cursor.pos)?;
+                            }
+                            ' ' => {
+                                editor.commit_shape(editor.selected_color)?;
+                                editor.set_mode(Mode::Selection);
                             }
                             _ => {}
-                        },
+                        }
+                    }
+                }
+                Input::KeyMouse => {
+                    match getmouse() {
+                        Ok(event) => {
+                            for event in mouse.update(&event) {
+                                mouse.handle(&mut editor, event)?;
+                            }
+                        }
                         Err(e) => {
-                            editor.mvprintw(40, 0, format!("{:?}", e));
+                            editor.draw_message(&format!("{:?}", e));
                         }
                     }
-                    editor.refresh();
                 }
                 _ => {}
             }
+            //everything the event changed reaches the terminal at once
+            editor.flush();
+        }
+        let now = Instant::now();
+        let mut ticked = false;
+        if autosave.as_mut().is_some_and(|timer| timer.is_due(now))
+            && !diff::changed_pixels(&saved, &editor.tif_image).is_empty()
+        {
+            //a file that cant be written anymore doesnt close the editor, the next autosave tries again
+            match editor.autosave() {
+                Ok(()) => saved = editor.tif_image.clone(),
+                Err(e) => editor.draw_message(&format!("ERROR: {:#}", e)),
+            }
+            ticked = true;
+        }
+        if blink.as_mut().is_some_and(|timer| timer.is_due(now)) && !editor.too_small {
+            editor.blink(!editor.cursor.blinked);
+            ticked = true;
+        }
+        if editor.selection.is_some() && march.is_due(now) && !editor.too_small {
+            editor.march();
+            ticked = true;
+        }
+        if ticked {
+            editor.flush();
         }
     }
-    println!("\033[?1003l\n");
+    print!("\x1b[?1002l");
+    std::io::stdout().flush()?;
     endwin();
-    let mut file = OpenOptions::new()
-        .truncate(true)
-        .create(true)
-        .write(true)
-        .open(args.file)?;
-    file.write(&editor.tif_image.save())?;
+    export::save_tif(&editor.tif_image, &editor.file)?;
+    if let Ok(session) = &mut session {
+        let state = FileState {
+            cursor: editor.cursor.pos,
+            palette,
+            selected_color: editor.selected_color,
+        };
+        session.remember(&key, state);
+        session.save(&options.session)?;
+    }
     Ok(())
 }
@@ -1,6 +1,28 @@
+use crate::shapes::Shape;
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum Mode {
     Insertion,
     Selection,
     Area,
+    Line,
+    Rectangle,
+    Remap,
+    ///typing an editor command after `:`
+    Command,
+    ///the clipboard or a stamp of the library follows the cursor until it is stamped
+    Paste,
+    ///the typed text follows the cursor in the selected color until it is drawn
+    Text,
+}
+
+impl Mode {
+    ///the shape drawn by the shape tool modes
+    pub fn shape(self) -> Option<Shape> {
+        match self {
+            Mode::Line => Some(Shape::Line),
+            Mode::Rectangle => Some(Shape::Rectangle),
+            _ => None,
+        }
+    }
 }
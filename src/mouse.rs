@@ -0,0 +1,147 @@
+use anyhow::Result;
+use pancurses::{
+    BUTTON1_CLICKED, BUTTON1_PRESSED, BUTTON1_RELEASED, BUTTON3_CLICKED, BUTTON3_PRESSED,
+    BUTTON3_RELEASED, BUTTON_CTRL, MEVENT, REPORT_MOUSE_POSITION,
+};
+
+use crate::editor::Editor;
+use crate::mode::Mode;
+use libtif::pixel::PixelColor;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MouseAction {
+    Press(Button),
+    Drag(Button),
+    Release(Button),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct MouseEvent {
+    pub action: MouseAction,
+    ///(y, x) in screen coordinates
+    pub pos: (i32, i32),
+    ///ctrl was held, the event is used to sample colors
+    pub ctrl: bool,
+}
+
+///curses only reports which button changed, this keeps track of the button being held
+///so position reports can be turned into drags
+pub struct MouseState {
+    held: Option<Button>,
+    ///the last press started a stroke inside of the canvas
+    stroke: bool,
+}
+
+impl MouseState {
+    pub fn new() -> Self {
+        Self {
+            held: None,
+            stroke: false,
+        }
+    }
+
+    pub fn update(&mut self, event: &MEVENT) -> Vec<MouseEvent> {
+        let pos = (event.y, event.x);
+        let ctrl = event.bstate & BUTTON_CTRL != 0;
+        let new_event = |action| MouseEvent { action, pos, ctrl };
+        let mut events = vec![];
+        for (button, pressed, released, clicked) in [
+            (Button::Left, BUTTON1_PRESSED, BUTTON1_RELEASED, BUTTON1_CLICKED),
+            (Button::Right, BUTTON3_PRESSED, BUTTON3_RELEASED, BUTTON3_CLICKED),
+        ] {
+            if event.bstate & (pressed | clicked) != 0 {
+                self.held = Some(button);
+                events.push(new_event(MouseAction::Press(button)));
+            }
+            if event.bstate & (released | clicked) != 0 {
+                self.held = None;
+                events.push(new_event(MouseAction::Release(button)));
+            }
+        }
+        if event.bstate & REPORT_MOUSE_POSITION != 0 {
+            if let Some(button) = self.held {
+                events.push(new_event(MouseAction::Drag(button)));
+            }
+        }
+        events
+    }
+
+    pub fn handle(&mut self, editor: &mut Editor, event: MouseEvent) -> Result<()> {
+        let mode = editor.get_mode();
+        let pos = editor.to_image(event.pos);
+        let clipped = editor.clip_to_image(pos);
+        match event.action {
+            MouseAction::Press(button) => {
+                if let Some(swatch) = editor.swatch_at(event.pos) {
+                    match button {
+                        _ if mode == Mode::Remap => editor.set_remap_target(swatch),
+                        Button::Left => editor.set_selected_color(swatch),
+                        Button::Right => editor.set_secondary_color(swatch),
+                    }
+                    return Ok(());
+                }
+                if let Some(pattern) = editor.pattern_at(event.pos) {
+                    editor.set_pattern(pattern);
+                    return Ok(());
+                }
+                //strokes only start inside of the canvas, they are clipped once they started
+                self.stroke = editor.is_in_image(pos);
+                if !self.stroke {
+                    return Ok(());
+                }
+                match mode {
+                    _ if event.ctrl || (button == Button::Right && mode == Mode::Selection) => {
+                        editor.pick_color_at(pos)?;
+                    }
+                    Mode::Insertion => editor.paint_at(pos, button_color(editor, button))?,
+                    Mode::Area => editor.anchor_area(pos)?,
+                    Mode::Line | Mode::Rectangle => editor.anchor_shape(pos)?,
+                    Mode::Selection | Mode::Text => editor.set_cursor_pos(pos)?,
+                    Mode::Paste => {
+                        editor.set_cursor_pos(pos)?;
+                        editor.stamp()?;
+                    }
+                    Mode::Remap | Mode::Command => {}
+                }
+            }
+            MouseAction::Drag(_) if !self.stroke => {}
+            MouseAction::Drag(button) => match mode {
+                _ if event.ctrl => editor.pick_color_at(clipped)?,
+                Mode::Insertion => editor.stroke_to(clipped, button_color(editor, button))?,
+                Mode::Area => {
+                    editor.set_cursor_pos(clipped)?;
+                    editor.set_area_based_on_current_cursor_position()?;
+                }
+                Mode::Line | Mode::Rectangle => {
+                    editor.set_cursor_pos(clipped)?;
+                    editor.set_shape_end(clipped)?;
+                }
+                Mode::Selection | Mode::Paste | Mode::Text => editor.set_cursor_pos(clipped)?,
+                Mode::Remap | Mode::Command => {}
+            },
+            MouseAction::Release(button) => {
+                if self.stroke && mode.shape().is_some() && !event.ctrl {
+                    editor.set_cursor_pos(clipped)?;
+                    editor.set_shape_end(clipped)?;
+                    editor.commit_shape(button_color(editor, button))?;
+                }
+                self.stroke = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+///left paints with the primary color, right with the secondary one
+fn button_color(editor: &Editor, button: Button) -> PixelColor {
+    match button {
+        Button::Left => editor.selected_color,
+        Button::Right => editor.secondary_color,
+    }
+}
@@ -1,7 +1,42 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
 use libtif::pixel::PixelColor;
+use pancurses::{
+    COLOR_BLACK, COLOR_BLUE, COLOR_CYAN, COLOR_GREEN, COLOR_MAGENTA, COLOR_RED, COLOR_WHITE,
+    COLOR_YELLOW,
+};
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    ///parses colors written as `#rrggbb`
+    pub fn parse(s: &str) -> Result<Self> {
+        let hex = s
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6)
+            .ok_or_else(|| anyhow!("expected a color like #rrggbb, found {:?}", s))?;
+        let channel = |i: usize| {
+            u8::from_str_radix(&hex[i..i + 2], 16).with_context(|| format!("invalid color {:?}", s))
+        };
+        Ok(Self(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    ///curses wants every channel in the 0..=1000 range
+    pub fn as_curses(&self) -> (i16, i16, i16) {
+        let scale = |c: u8| (c as i32 * 1000 / 255) as i16;
+        (scale(self.0), scale(self.1), scale(self.2))
+    }
+}
+
+///`colors` is the only mapping between colors, palette slots and color pairs:
+///the color at slot `i` is picked with the digit `i + 1` and drawn with the color pair `i + 1`
+///(pair 0 cant be redefined by curses), the grid over it uses the pair `i + 11`
 pub struct Pallete {
-    pub colors: Vec<PixelColor>
+    pub colors: Vec<PixelColor>,
+    ///true color value of every slot, `rgb[i]` belongs to `colors[i]`
+    pub rgb: Vec<Rgb>,
 }
 
 impl Pallete {
@@ -9,6 +44,118 @@ impl Pallete {
         use PixelColor::*;
         Self {
             colors: vec![Black, Red, Green, Yellow, Blue, Magenta, Cyan, White],
+            rgb: vec![
+                Rgb(0x00, 0x00, 0x00),
+                Rgb(0xcd, 0x00, 0x00),
+                Rgb(0x00, 0xcd, 0x00),
+                Rgb(0xcd, 0xcd, 0x00),
+                Rgb(0x00, 0x00, 0xee),
+                Rgb(0xcd, 0x00, 0xcd),
+                Rgb(0x00, 0xcd, 0xcd),
+                Rgb(0xe5, 0xe5, 0xe5),
+            ],
+        }
+    }
+
+    ///a palette file has one `<color name> #rrggbb` entry per line,
+    ///slots that aren't listed keep their default value
+    ///```text
+    ///# comment
+    ///black  #1a1c2c
+    ///red    #b13e53
+    ///```
+    pub fn from_file(path: &str) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("couldnt read palette file {}", path))?;
+        let mut pallete = Self::new();
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut fields = line.split_whitespace();
+            let (name, value) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(name), Some(value), None) => (name, value),
+                _ => return Err(anyhow!("{}:{}: expected `<color> #rrggbb`", path, n + 1)),
+            };
+            let color = parse_color_name(name)
+                .ok_or_else(|| anyhow!("{}:{}: unknown color {:?}", path, n + 1, name))?;
+            let rgb = Rgb::parse(value).with_context(|| format!("{}:{}", path, n + 1))?;
+            let slot = pallete.slot_of(&color);
+            pallete.rgb[slot] = rgb;
         }
+        Ok(pallete)
+    }
+
+    pub fn slot_of(&self, color: &PixelColor) -> usize {
+        self.colors
+            .iter()
+            .position(|c| c == color)
+            .expect("every PixelColor has a slot in the pallete")
+    }
+
+    pub fn rgb_of(&self, color: &PixelColor) -> Rgb {
+        self.rgb[self.slot_of(color)]
+    }
+
+    pub fn pair_of(&self, color: &PixelColor) -> u32 {
+        self.slot_of(color) as u32 + 1
+    }
+
+    pub fn grid_pair_of(&self, color: &PixelColor) -> u32 {
+        self.slot_of(color) as u32 + 11
+    }
+
+    ///dark text is easier to read than light text over `color`
+    pub fn is_light(&self, color: &PixelColor) -> bool {
+        let Rgb(r, g, b) = self.rgb_of(color);
+        r as u32 * 299 + g as u32 * 587 + b as u32 * 114 > 128_000
+    }
+
+    pub fn color_at(&self, slot: usize) -> Option<PixelColor> {
+        self.colors.get(slot).copied()
     }
+
+    ///the color of the pallete that looks the most like `rgb`
+    pub fn nearest(&self, rgb: Rgb) -> PixelColor {
+        let distance = |other: &Rgb| {
+            [(rgb.0, other.0), (rgb.1, other.1), (rgb.2, other.2)]
+                .iter()
+                .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+                .sum::<i32>()
+        };
+        let slot = (0..self.colors.len())
+            .min_by_key(|slot| distance(&self.rgb[*slot]))
+            .unwrap_or_default();
+        self.colors[slot]
+    }
+}
+
+///the terminal color used to show `color` when the palette cant be applied
+pub fn curses_color(color: &PixelColor) -> i16 {
+    match color {
+        PixelColor::Black => COLOR_BLACK,
+        PixelColor::Red => COLOR_RED,
+        PixelColor::Green => COLOR_GREEN,
+        PixelColor::Yellow => COLOR_YELLOW,
+        PixelColor::Blue => COLOR_BLUE,
+        PixelColor::Magenta => COLOR_MAGENTA,
+        PixelColor::Cyan => COLOR_CYAN,
+        PixelColor::White => COLOR_WHITE,
+    }
+}
+
+pub fn parse_color_name(name: &str) -> Option<PixelColor> {
+    use PixelColor::*;
+    Some(match name.to_ascii_lowercase().as_str() {
+        "black" => Black,
+        "red" => Red,
+        "green" => Green,
+        "yellow" => Yellow,
+        "blue" => Blue,
+        "magenta" => Magenta,
+        "cyan" => Cyan,
+        "white" => White,
+        _ => return None,
+    })
 }
@@ -0,0 +1,58 @@
+use libtif::pixel::PixelColor;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Pattern {
+    Solid,
+    ///2x2 blocks of each color
+    Checkerboard,
+    Dither25,
+    Dither50,
+    Dither75,
+    HorizontalStripes,
+    VerticalStripes,
+}
+
+///the order of the on screen pattern list
+pub const PATTERNS: [Pattern; 7] = [
+    Pattern::Solid,
+    Pattern::Checkerboard,
+    Pattern::Dither25,
+    Pattern::Dither50,
+    Pattern::Dither75,
+    Pattern::HorizontalStripes,
+    Pattern::VerticalStripes,
+];
+
+///2x2 bayer matrix used by the ordered dithers
+const BAYER: [[u8; 2]; 2] = [[0, 2], [3, 1]];
+
+impl Pattern {
+    pub fn next(self) -> Self {
+        let i = PATTERNS.iter().position(|p| *p == self).unwrap_or(0);
+        PATTERNS[(i + 1) % PATTERNS.len()]
+    }
+
+    ///whether `pos` gets the color being painted, otherwise it gets the other color
+    fn is_foreground(self, pos: (usize, usize)) -> bool {
+        let (y, x) = pos;
+        let threshold = BAYER[y % 2][x % 2];
+        match self {
+            Pattern::Solid => true,
+            Pattern::Checkerboard => (y / 2 + x / 2) % 2 == 0,
+            Pattern::Dither25 => threshold < 1,
+            Pattern::Dither50 => threshold < 2,
+            Pattern::Dither75 => threshold < 3,
+            Pattern::HorizontalStripes => y % 2 == 0,
+            Pattern::VerticalStripes => x % 2 == 0,
+        }
+    }
+
+    ///the color of `pos` when painting with `color` over `other`
+    pub fn color_at(self, pos: (usize, usize), color: PixelColor, other: PixelColor) -> PixelColor {
+        if self.is_foreground(pos) {
+            color
+        } else {
+            other
+        }
+    }
+}
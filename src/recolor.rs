@@ -0,0 +1,51 @@
+use libtif::image::TifImage;
+use libtif::pixel::PixelColor;
+
+use crate::pallete::Pallete;
+
+///positions of the pixels of color `color`
+pub fn matching(tif_image: &TifImage, color: PixelColor) -> Vec<(usize, usize)> {
+    let mut positions = vec![];
+    for (y, row) in tif_image.pixels.iter().enumerate() {
+        for (x, pixel) in row.iter().enumerate() {
+            if *pixel == color {
+                positions.push((y, x));
+            }
+        }
+    }
+    positions
+}
+
+///a palette swap: the color at every palette slot is replaced by its target
+pub struct Remap {
+    ///`targets[i]` replaces the color at the slot `i` of the palette
+    pub targets: Vec<PixelColor>,
+    ///the slot being edited
+    pub slot: usize,
+}
+
+impl Remap {
+    ///every color is mapped to itself
+    pub fn identity(pallete: &Pallete) -> Self {
+        Self {
+            targets: pallete.colors.clone(),
+            slot: 0,
+        }
+    }
+
+    pub fn target_of(&self, pallete: &Pallete, color: &PixelColor) -> PixelColor {
+        self.targets[pallete.slot_of(color)]
+    }
+
+    ///set the target of the current slot and move to the next one
+    pub fn set_target(&mut self, color: PixelColor) {
+        self.targets[self.slot] = color;
+        self.slot = (self.slot + 1) % self.targets.len();
+    }
+
+    pub fn apply(&self, tif_image: &mut TifImage, pallete: &Pallete) {
+        for pixel in tif_image.pixels.iter_mut().flatten() {
+            *pixel = self.target_of(pallete, pixel);
+        }
+    }
+}
@@ -0,0 +1,137 @@
+use std::cell::RefCell;
+
+use pancurses::{chtype, resize_term, Input, Window};
+
+///where the editor draws: the terminal, or a grid of characters when there is no terminal (tests)
+pub enum Screen {
+    Terminal(Window),
+    #[cfg_attr(not(test), allow(dead_code))]
+    Headless(RefCell<Vec<Vec<char>>>),
+}
+
+impl Screen {
+    #[cfg_attr(not(test), allow(dead_code))]
+    pub fn headless(height: i32, width: i32) -> Self {
+        Screen::Headless(RefCell::new(vec![
+            vec![' '; width as usize];
+            height as usize
+        ]))
+    }
+
+    ///writes `s` from (y, x), clipped to the screen
+    fn put(cells: &RefCell<Vec<Vec<char>>>, y: i32, x: i32, s: &str) {
+        let mut cells = cells.borrow_mut();
+        let Some(row) = usize::try_from(y).ok().and_then(|y| cells.get_mut(y)) else {
+            return;
+        };
+        for (i, c) in s.chars().enumerate() {
+            if let Some(cell) = usize::try_from(x + i as i32).ok().and_then(|x| row.get_mut(x)) {
+                *cell = c;
+            }
+        }
+    }
+
+    pub fn mvprintw<T: AsRef<str>>(&self, y: i32, x: i32, string: T) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.mvprintw(y, x, string),
+            Screen::Headless(cells) => {
+                Self::put(cells, y, x, string.as_ref());
+                0
+            }
+        }
+    }
+
+    pub fn mvaddstr<T: AsRef<str>>(&self, y: i32, x: i32, string: T) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.mvaddstr(y, x, string),
+            Screen::Headless(cells) => {
+                Self::put(cells, y, x, string.as_ref());
+                0
+            }
+        }
+    }
+
+    pub fn mvaddch(&self, y: i32, x: i32, c: char) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.mvaddch(y, x, c),
+            Screen::Headless(cells) => {
+                Self::put(cells, y, x, c.encode_utf8(&mut [0; 4]));
+                0
+            }
+        }
+    }
+
+    pub fn attrset(&self, attributes: chtype) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.attrset(attributes),
+            Screen::Headless(_) => 0,
+        }
+    }
+
+    pub fn attroff(&self, attributes: chtype) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.attroff(attributes),
+            Screen::Headless(_) => 0,
+        }
+    }
+
+    pub fn clear(&self) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.clear(),
+            Screen::Headless(cells) => {
+                for row in cells.borrow_mut().iter_mut() {
+                    row.fill(' ');
+                }
+                0
+            }
+        }
+    }
+
+    pub fn refresh(&self) -> i32 {
+        match self {
+            Screen::Terminal(window) => window.refresh(),
+            Screen::Headless(_) => 0,
+        }
+    }
+
+    pub fn get_max_yx(&self) -> (i32, i32) {
+        match self {
+            Screen::Terminal(window) => window.get_max_yx(),
+            Screen::Headless(cells) => {
+                let cells = cells.borrow();
+                (cells.len() as i32, cells.first().map_or(0, |row| row.len() as i32))
+            }
+        }
+    }
+
+    ///picks up the new size of the terminal after a KEY_RESIZE
+    pub fn resize(&self) {
+        if let Screen::Terminal(_) = self {
+            resize_term(0, 0);
+        }
+    }
+
+    ///how long `getch` waits for input, in milliseconds, -1 waits until there is some
+    pub fn timeout(&self, milliseconds: i32) {
+        if let Screen::Terminal(window) = self {
+            window.timeout(milliseconds);
+        }
+    }
+
+    ///there is never any input without a terminal
+    pub fn getch(&self) -> Option<Input> {
+        match self {
+            Screen::Terminal(window) => window.getch(),
+            Screen::Headless(_) => None,
+        }
+    }
+
+    ///the characters of row `y`, without the colors
+    #[cfg(test)]
+    pub fn line(&self, y: i32) -> String {
+        match self {
+            Screen::Terminal(_) => String::new(),
+            Screen::Headless(cells) => cells.borrow()[y as usize].iter().collect(),
+        }
+    }
+}
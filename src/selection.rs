@@ -0,0 +1,272 @@
+use libtif::image::TifImage;
+use libtif::pixel::PixelColor;
+
+use crate::area::Point;
+use crate::diff::Bounds;
+use crate::fill;
+use crate::shapes;
+
+///how Area mode picks the pixels
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SelectionTool {
+    ///every pixel between the anchor and the cursor
+    Rectangle,
+    ///the shape drawn by the cursor, closed from its end back to the anchor
+    Lasso,
+    ///the same colored pixels connected to the cursor
+    Wand,
+}
+
+impl SelectionTool {
+    pub fn next(self) -> Self {
+        match self {
+            SelectionTool::Rectangle => SelectionTool::Lasso,
+            SelectionTool::Lasso => SelectionTool::Wand,
+            SelectionTool::Wand => SelectionTool::Rectangle,
+        }
+    }
+}
+
+///what a new selection does to the one there was before
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Combine {
+    Replace,
+    Add,
+    Subtract,
+}
+
+///the selected pixels of the image
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Selection {
+    mask: Vec<Vec<bool>>,
+}
+
+impl Selection {
+    pub fn empty(image: (u64, u8)) -> Self {
+        Self {
+            mask: vec![vec![false; image.1 as usize]; image.0 as usize],
+        }
+    }
+
+    pub fn all(image: (u64, u8)) -> Self {
+        Self {
+            mask: vec![vec![true; image.1 as usize]; image.0 as usize],
+        }
+    }
+
+    ///both corners included, clipped to the image
+    pub fn rectangle(image: (u64, u8), from: (i32, i32), to: (i32, i32)) -> Self {
+        let mut selection = Self::empty(image);
+        for y in from.0.min(to.0)..=from.0.max(to.0) {
+            for x in from.1.min(to.1)..=from.1.max(to.1) {
+                selection.select((y, x));
+            }
+        }
+        selection
+    }
+
+    ///the pixels on the path through `points` (closed back to the first one) and the ones it surrounds
+    pub fn lasso(image: (u64, u8), points: &[(i32, i32)]) -> Self {
+        let mut selection = Self::empty(image);
+        let Some(first) = points.first() else {
+            return selection;
+        };
+        let edges: Vec<_> = points.iter().zip(points.iter().skip(1).chain([first])).collect();
+        for (from, to) in &edges {
+            for point in shapes::line(Point::new(from.0, from.1), Point::new(to.0, to.1)) {
+                selection.select((point.y, point.x));
+            }
+        }
+        //even-odd rule: a pixel is inside when a ray going right from it crosses the path an odd number of times
+        for (y, row) in selection.mask.iter_mut().enumerate() {
+            let (py, mut crossings) = (y as f64, vec![]);
+            for (from, to) in &edges {
+                let (ay, by) = (from.0 as f64, to.0 as f64);
+                if (ay > py) != (by > py) {
+                    crossings.push(from.1 as f64 + (py - ay) * (to.1 - from.1) as f64 / (by - ay));
+                }
+            }
+            for (x, selected) in row.iter_mut().enumerate() {
+                if crossings.iter().filter(|crossing| **crossing > x as f64).count() % 2 == 1 {
+                    *selected = true;
+                }
+            }
+        }
+        selection
+    }
+
+    ///magic wand, like the bucket fill
+    pub fn wand(tif_image: &TifImage, pos: (i32, i32)) -> Self {
+        let mut selection = Self::empty((tif_image.height, tif_image.width));
+        if pos.0 >= 0 && pos.1 >= 0 {
+            for (y, x) in fill::flood(tif_image, (pos.0 as usize, pos.1 as usize)) {
+                selection.select((y as i32, x as i32));
+            }
+        }
+        selection
+    }
+
+    ///(height, width) of the image it was made for
+    pub fn size(&self) -> (u64, u8) {
+        (
+            self.mask.len() as u64,
+            self.mask.first().map_or(0, |row| row.len() as u8),
+        )
+    }
+
+    fn select(&mut self, (y, x): (i32, i32)) {
+        if let Some(selected) = usize::try_from(y)
+            .ok()
+            .zip(usize::try_from(x).ok())
+            .and_then(|(y, x)| self.mask.get_mut(y)?.get_mut(x))
+        {
+            *selected = true;
+        }
+    }
+
+    pub fn contains(&self, (y, x): (usize, usize)) -> bool {
+        self.mask.get(y).and_then(|row| row.get(x)).copied().unwrap_or(false)
+    }
+
+    ///a selected pixel next to one that isnt (or to the border of the image), the outline is drawn there
+    pub fn is_edge(&self, (y, x): (usize, usize)) -> bool {
+        self.contains((y, x))
+            && (y == 0
+                || x == 0
+                || !self.contains((y - 1, x))
+                || !self.contains((y + 1, x))
+                || !self.contains((y, x - 1))
+                || !self.contains((y, x + 1)))
+    }
+
+    ///`self` changed by `other`
+    pub fn combine(mut self, other: &Selection, combine: Combine) -> Self {
+        if combine == Combine::Replace {
+            return other.clone();
+        }
+        for (row, other_row) in self.mask.iter_mut().zip(&other.mask) {
+            for (selected, other) in row.iter_mut().zip(other_row) {
+                match combine {
+                    Combine::Add => *selected |= other,
+                    Combine::Subtract => *selected &= !other,
+                    Combine::Replace => {}
+                }
+            }
+        }
+        self
+    }
+
+    pub fn invert(&mut self) {
+        for selected in self.mask.iter_mut().flatten() {
+            *selected = !*selected;
+        }
+    }
+
+    ///every selected position, row by row
+    pub fn positions(&self) -> Vec<(usize, usize)> {
+        let mut positions = vec![];
+        for (y, row) in self.mask.iter().enumerate() {
+            for (x, selected) in row.iter().enumerate() {
+                if *selected {
+                    positions.push((y, x));
+                }
+            }
+        }
+        positions
+    }
+
+    pub fn count(&self) -> usize {
+        self.mask.iter().flatten().filter(|selected| **selected).count()
+    }
+
+    ///the pixels inside of the bounds of the selection, the ones that arent selected get `background`.
+    ///`None` when nothing is selected
+    pub fn extract(&self, tif_image: &TifImage, background: PixelColor) -> Option<TifImage> {
+        let bounds = self.bounds()?;
+        let mut extracted = crop(tif_image, bounds);
+        let ((top, left), _) = bounds;
+        for (y, row) in extracted.pixels.iter_mut().enumerate() {
+            for (x, pixel) in row.iter_mut().enumerate() {
+                if !self.contains((top + y, left + x)) {
+                    *pixel = background;
+                }
+            }
+        }
+        Some(extracted)
+    }
+
+    ///bounds of the selected pixels, `None` when there are none
+    pub fn bounds(&self) -> Option<Bounds> {
+        let positions = self.positions();
+        let first = *positions.first()?;
+        Some(positions.iter().fold((first, first), |(top_left, bottom_right), (y, x)| {
+            (
+                (top_left.0.min(*y), top_left.1.min(*x)),
+                (bottom_right.0.max(*y), bottom_right.1.max(*x)),
+            )
+        }))
+    }
+}
+
+///the pixels inside of `bounds`
+pub fn crop(tif_image: &TifImage, ((top, left), (bottom, right)): Bounds) -> TifImage {
+    let pixels: Vec<Vec<PixelColor>> = tif_image.pixels[top..=bottom]
+        .iter()
+        .map(|row| row[left..=right].to_vec())
+        .collect();
+    TifImage {
+        height: pixels.len() as u64,
+        width: (right - left + 1) as u8,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(selection: &Selection) -> Vec<String> {
+        selection
+            .mask
+            .iter()
+            .map(|row| row.iter().map(|selected| if *selected { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn lasso_selects_the_inside() {
+        let selection = Selection::lasso((5, 5), &[(0, 0), (0, 4), (4, 4), (4, 0)]);
+        assert_eq!(selection.count(), 25);
+        let triangle = Selection::lasso((4, 7), &[(0, 3), (3, 6), (3, 0)]);
+        assert_eq!(rows(&triangle), ["...#...", "..###..", ".#####.", "#######"]);
+    }
+
+    #[test]
+    fn combine_and_invert() {
+        let big = Selection::rectangle((3, 4), (0, 0), (2, 2));
+        let small = Selection::rectangle((3, 4), (1, 1), (5, 5));
+        let added = Selection::empty((3, 4)).combine(&big, Combine::Add).combine(&small, Combine::Add);
+        assert_eq!(rows(&added), ["###.", "####", "####"]);
+        let mut subtracted = big.clone().combine(&small, Combine::Subtract);
+        assert_eq!(rows(&subtracted), ["###.", "#...", "#..."]);
+        assert_eq!(subtracted.bounds(), Some(((0, 0), (2, 2))));
+        subtracted.invert();
+        assert_eq!(rows(&subtracted), ["...#", ".###", ".###"]);
+        assert_eq!(big.combine(&small, Combine::Replace), small);
+    }
+
+    #[test]
+    fn wand_and_edges() {
+        let mut tif_image = TifImage {
+            height: 3,
+            width: 3,
+            pixels: vec![vec![PixelColor::Black; 3]; 3],
+        };
+        tif_image.pixels[1][2] = PixelColor::Red;
+        let selection = Selection::wand(&tif_image, (0, 0));
+        assert_eq!(rows(&selection), ["###", "##.", "###"]);
+        assert!(Selection::all((3, 3)).is_edge((0, 1)));
+        assert!(!Selection::all((3, 3)).is_edge((1, 1)));
+        assert!(selection.is_edge((1, 1)));
+    }
+}
@@ -0,0 +1,178 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use libtif::pixel::PixelColor;
+
+use crate::pallete;
+
+///where the session is kept when `--session` isnt given, in the directory the editor runs from
+pub const DEFAULT_PATH: &str = ".tif_editor_session";
+///files remembered, the oldest ones are forgotten
+const MAX_RECENT: usize = 20;
+
+///how a file was left when the editor closed. there is no zoom to remember, a pixel is always one cell
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FileState {
+    pub cursor: (i32, i32),
+    ///the `--palette` file it was edited with
+    pub palette: Option<String>,
+    pub selected_color: PixelColor,
+}
+
+///the recently opened files, the last one first. one line per file in the session file:
+///`path<TAB>y x<TAB>color<TAB>palette`, the palette is empty without a palette file.
+///backslashes, tabs and line breaks of the paths are escaped as `\\`, `\t`, `\n` and `\r`
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct Session {
+    files: Vec<(String, FileState)>,
+}
+
+impl Session {
+    ///an empty session when the file doesnt exist yet
+    pub fn load(path: &str) -> Result<Self> {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::parse(&text).with_context(|| format!("couldnt read the session {}", path)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e).with_context(|| format!("couldnt read the session {}", path)),
+        }
+    }
+
+    pub fn save(&self, path: &str) -> Result<()> {
+        std::fs::write(path, self.to_string()).with_context(|| format!("couldnt write the session {}", path))
+    }
+
+    pub fn parse(text: &str) -> Result<Self> {
+        let mut files = vec![];
+        for (i, line) in text.lines().enumerate() {
+            if line.is_empty() {
+                continue;
+            }
+            let fields: Vec<&str> = line.split('\t').collect();
+            let [file, cursor, color, palette] = fields[..] else {
+                return Err(anyhow!("line {}: expected 4 fields separated by tabs", i + 1));
+            };
+            let cursor = cursor
+                .split_once(' ')
+                .and_then(|(y, x)| Some((y.parse().ok()?, x.parse().ok()?)))
+                .ok_or_else(|| anyhow!("line {}: bad cursor position {:?}", i + 1, cursor))?;
+            let selected_color = pallete::parse_color_name(color)
+                .ok_or_else(|| anyhow!("line {}: unknown color {:?}", i + 1, color))?;
+            let state = FileState {
+                cursor,
+                palette: (!palette.is_empty()).then(|| unescape(palette)),
+                selected_color,
+            };
+            files.push((unescape(file), state));
+        }
+        Ok(Self { files })
+    }
+
+    pub fn get(&self, file: &str) -> Option<&FileState> {
+        self.files.iter().find(|(path, _)| path == file).map(|(_, state)| state)
+    }
+
+    ///`file` becomes the most recent one
+    pub fn remember(&mut self, file: &str, state: FileState) {
+        self.files.retain(|(path, _)| path != file);
+        self.files.insert(0, (file.to_string(), state));
+        self.files.truncate(MAX_RECENT);
+    }
+
+    ///the last opened file first
+    pub fn recent(&self) -> impl Iterator<Item = &str> {
+        self.files.iter().map(|(path, _)| path.as_str())
+    }
+}
+
+impl std::fmt::Display for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (file, state) in &self.files {
+            writeln!(
+                f,
+                "{}\t{} {}\t{:?}\t{}",
+                escape(file),
+                state.cursor.0,
+                state.cursor.1,
+                state.selected_color,
+                escape(state.palette.as_deref().unwrap_or(""))
+            )?;
+        }
+        Ok(())
+    }
+}
+
+///a path without the separators of the session file
+fn escape(path: &str) -> String {
+    path.replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+}
+
+fn unescape(path: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = path.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => unescaped.push('\t'),
+            Some('n') => unescaped.push('\n'),
+            Some('r') => unescaped.push('\r'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+///the files are remembered by their absolute path, so they are found from any directory
+pub fn absolute(file: &str) -> String {
+    std::fs::canonicalize(file)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| file.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn state(y: i32, palette: Option<&str>) -> FileState {
+        FileState {
+            cursor: (y, 3),
+            palette: palette.map(String::from),
+            selected_color: PixelColor::Cyan,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut session = Session::default();
+        session.remember("/a b.tif", state(1, None));
+        session.remember("/b.tif", state(2, Some("/pal ette.txt")));
+        session.remember("/tab\there\nand\\there\r.tif", state(3, Some("C:\\pal\\n.txt")));
+        assert_eq!(session.to_string().lines().count(), 3);
+        let parsed = Session::parse(&session.to_string()).unwrap();
+        assert_eq!(parsed, session);
+        assert_eq!(parsed.get("/a b.tif"), Some(&state(1, None)));
+        assert!(Session::parse("/a.tif\t1 2\tPink\t\n").is_err());
+        assert!(Session::parse("/a.tif\t1\tRed\t\n").is_err());
+        assert_eq!(Session::load("does/not/exist").unwrap(), Session::default());
+    }
+
+    #[test]
+    fn last_opened_file_comes_first() {
+        let mut session = Session::default();
+        for i in 0..=MAX_RECENT as i32 {
+            session.remember(&format!("{}.tif", i), state(i, None));
+        }
+        session.remember("5.tif", state(-1, None));
+        let recent: Vec<_> = session.recent().collect();
+        assert_eq!(recent.len(), MAX_RECENT);
+        assert_eq!(recent[..2], ["5.tif", &format!("{}.tif", MAX_RECENT)]);
+        assert!(!recent.contains(&"0.tif"));
+        assert_eq!(session.get("5.tif").unwrap().cursor, (-1, 3));
+    }
+}
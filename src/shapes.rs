@@ -0,0 +1,53 @@
+use crate::area::Point;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Shape {
+    Line,
+    Rectangle,
+}
+
+impl Shape {
+    pub fn points(self, from: Point, to: Point) -> Vec<Point> {
+        match self {
+            Shape::Line => line(from, to),
+            Shape::Rectangle => rectangle(from, to),
+        }
+    }
+}
+
+///bresenham's line, both ends included
+pub fn line(from: Point, to: Point) -> Vec<Point> {
+    let dx = (to.x - from.x).abs();
+    let dy = -(to.y - from.y).abs();
+    let step_x = if from.x < to.x { 1 } else { -1 };
+    let step_y = if from.y < to.y { 1 } else { -1 };
+    let mut err = dx + dy;
+    let mut current = from;
+    let mut points = vec![current];
+    while current.x != to.x || current.y != to.y {
+        let e2 = 2 * err;
+        if e2 >= dy {
+            err += dy;
+            current.x += step_x;
+        }
+        if e2 <= dx {
+            err += dx;
+            current.y += step_y;
+        }
+        points.push(current);
+    }
+    points
+}
+
+///outline of the rectangle that has `from` and `to` as opposite corners
+pub fn rectangle(from: Point, to: Point) -> Vec<Point> {
+    let top_left = Point::new(from.y.min(to.y), from.x.min(to.x));
+    let bottom_right = Point::new(from.y.max(to.y), from.x.max(to.x));
+    let top_right = Point::new(top_left.y, bottom_right.x);
+    let bottom_left = Point::new(bottom_right.y, top_left.x);
+    let mut points = line(top_left, top_right);
+    points.extend(line(top_right, bottom_right));
+    points.extend(line(bottom_right, bottom_left));
+    points.extend(line(bottom_left, top_left));
+    points
+}
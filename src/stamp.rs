@@ -0,0 +1,130 @@
+use anyhow::anyhow;
+use anyhow::Context;
+use anyhow::Result;
+use libtif::image::TifImage;
+use libtif::pixel::PixelColor;
+
+use crate::export;
+use crate::selection::Selection;
+
+///pixels pasted with their top left corner at the cursor, the ones without a color are transparent
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Stamp {
+    pub pixels: Vec<Vec<Option<PixelColor>>>,
+}
+
+impl Stamp {
+    ///the selected pixels, inside of the bounds of the selection. `None` when nothing is selected
+    pub fn from_selection(tif_image: &TifImage, selection: &Selection) -> Option<Self> {
+        let ((top, left), (bottom, right)) = selection.bounds()?;
+        let pixels = (top..=bottom)
+            .map(|y| {
+                (left..=right)
+                    .map(|x| selection.contains((y, x)).then(|| tif_image.pixels[y][x]))
+                    .collect()
+            })
+            .collect();
+        Some(Self { pixels })
+    }
+
+    ///the pixels of `transparent` are left out
+    pub fn from_tif(tif_image: &TifImage, transparent: PixelColor) -> Self {
+        let pixels = tif_image
+            .pixels
+            .iter()
+            .map(|row| row.iter().map(|pixel| (*pixel != transparent).then_some(*pixel)).collect())
+            .collect();
+        Self { pixels }
+    }
+
+    ///image position and color of every pixel that isnt transparent, when stamped at `pos`
+    pub fn pixels_at(&self, pos: (i32, i32)) -> Vec<((i32, i32), PixelColor)> {
+        let mut pixels = vec![];
+        for (y, row) in self.pixels.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                if let Some(color) = pixel {
+                    pixels.push(((pos.0 + y as i32, pos.1 + x as i32), *color));
+                }
+            }
+        }
+        pixels
+    }
+}
+
+///the .tif files of a directory, one of them is picked to be stamped
+pub struct Library {
+    pub dir: String,
+    ///file names, sorted
+    pub names: Vec<String>,
+    pub selected: usize,
+}
+
+impl Library {
+    pub fn open(dir: &str) -> Result<Self> {
+        let mut names: Vec<String> = std::fs::read_dir(dir)
+            .with_context(|| format!("couldnt open the stamp library {}", dir))?
+            .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+            .filter(|name| export::extension(name).as_deref() == Some("tif"))
+            .collect();
+        if names.is_empty() {
+            return Err(anyhow!("there are no .tif files in {}", dir));
+        }
+        names.sort();
+        Ok(Self {
+            dir: dir.to_string(),
+            names,
+            selected: 0,
+        })
+    }
+
+    ///moves the selection by `offset`, going around at both ends
+    pub fn select(&mut self, offset: i32) {
+        let len = self.names.len() as i32;
+        self.selected = (self.selected as i32 + offset).rem_euclid(len) as usize;
+    }
+
+    ///the selected stamp, the pixels of `transparent` are left out
+    pub fn load(&self, transparent: PixelColor) -> Result<Stamp> {
+        let path = std::path::Path::new(&self.dir).join(&self.names[self.selected]);
+        let tif_image = export::open_tif(&path.to_string_lossy())?;
+        Ok(Stamp::from_tif(&tif_image, transparent))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transparent_pixels_are_left_out() {
+        let tif_image = TifImage {
+            height: 2,
+            width: 2,
+            pixels: vec![
+                vec![PixelColor::White, PixelColor::Red],
+                vec![PixelColor::Red, PixelColor::Blue],
+            ],
+        };
+        let stamp = Stamp::from_tif(&tif_image, PixelColor::White);
+        assert_eq!(
+            stamp.pixels_at((5, -1)),
+            [((5, 0), PixelColor::Red), ((6, -1), PixelColor::Red), ((6, 0), PixelColor::Blue)]
+        );
+        let selection = Selection::rectangle((2, 2), (1, 0), (1, 0));
+        let stamp = Stamp::from_selection(&tif_image, &selection).unwrap();
+        assert_eq!(stamp.pixels, [[Some(PixelColor::Red)]]);
+    }
+
+    #[test]
+    fn library_lists_the_tif_files() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        let mut library = Library::open(dir).unwrap();
+        assert_eq!(library.names[0], "black_10x10.tif");
+        library.select(-1);
+        assert_eq!(library.selected, library.names.len() - 1);
+        library.selected = library.names.iter().position(|name| name == "red_1x1.tif").unwrap();
+        let stamp = library.load(PixelColor::White).unwrap();
+        assert_eq!(stamp.pixels, [[Some(PixelColor::Red)]]);
+        assert!(Library::open("does/not/exist").is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use libtif::image::TifImage;
+use libtif::pixel::PixelColor;
+
+use crate::diff::Bounds;
+use crate::info;
+use crate::pallete::Pallete;
+
+///pixel counts shown by the statistics panel, kept up to date pixel by pixel
+pub struct Stats {
+    ///pixels of every color, indexed by pallete slot
+    pub counts: Vec<usize>,
+    ///pixels of this color are left out of the bounds of the content
+    pub background: PixelColor,
+    ///pixels that are not background in every row
+    rows: Vec<usize>,
+    ///pixels that are not background in every column
+    columns: Vec<usize>,
+}
+
+impl Stats {
+    pub fn new(tif_image: &TifImage, pallete: &Pallete, background: PixelColor) -> Self {
+        let mut rows = vec![0; tif_image.height as usize];
+        let mut columns = vec![0; tif_image.width as usize];
+        for (y, row) in tif_image.pixels.iter().enumerate() {
+            for (x, pixel) in row.iter().enumerate() {
+                if *pixel != background {
+                    rows[y] += 1;
+                    columns[x] += 1;
+                }
+            }
+        }
+        Self {
+            counts: info::histogram(tif_image, pallete),
+            background,
+            rows,
+            columns,
+        }
+    }
+
+    ///the pixel at `pos` went from `old` to `new`
+    pub fn update(&mut self, pallete: &Pallete, pos: (usize, usize), old: PixelColor, new: PixelColor) {
+        self.counts[pallete.slot_of(&old)] -= 1;
+        self.counts[pallete.slot_of(&new)] += 1;
+        match (old == self.background, new == self.background) {
+            (true, false) => {
+                self.rows[pos.0] += 1;
+                self.columns[pos.1] += 1;
+            }
+            (false, true) => {
+                self.rows[pos.0] -= 1;
+                self.columns[pos.1] -= 1;
+            }
+            _ => {}
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+
+    ///bounds of the pixels that are not background, `None` when there are none
+    pub fn content_bounds(&self) -> Option<Bounds> {
+        let first = |counts: &[usize]| counts.iter().position(|count| *count > 0);
+        let last = |counts: &[usize]| counts.iter().rposition(|count| *count > 0);
+        Some((
+            (first(&self.rows)?, first(&self.columns)?),
+            (last(&self.rows)?, last(&self.columns)?),
+        ))
+    }
+}
@@ -0,0 +1,47 @@
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Symmetry {
+    None,
+    ///mirrors left <-> right, the axis is a vertical line in the middle of the image
+    Horizontal,
+    ///mirrors top <-> bottom, the axis is a horizontal line in the middle of the image
+    Vertical,
+    Both,
+}
+
+impl Symmetry {
+    pub fn next(self) -> Self {
+        match self {
+            Symmetry::None => Symmetry::Horizontal,
+            Symmetry::Horizontal => Symmetry::Vertical,
+            Symmetry::Vertical => Symmetry::Both,
+            Symmetry::Both => Symmetry::None,
+        }
+    }
+
+    pub fn mirrors_horizontally(self) -> bool {
+        matches!(self, Symmetry::Horizontal | Symmetry::Both)
+    }
+
+    pub fn mirrors_vertically(self) -> bool {
+        matches!(self, Symmetry::Vertical | Symmetry::Both)
+    }
+
+    ///every position that has to be painted together with `pos` (`pos` included)
+    pub fn mirror(self, pos: (usize, usize), image: (u64, u8)) -> Vec<(usize, usize)> {
+        let mirrored_y = (image.0 as usize).saturating_sub(pos.0 + 1);
+        let mirrored_x = (image.1 as usize).saturating_sub(pos.1 + 1);
+        let mut positions = vec![pos];
+        if self.mirrors_horizontally() {
+            positions.push((pos.0, mirrored_x));
+        }
+        if self.mirrors_vertically() {
+            positions.push((mirrored_y, pos.1));
+        }
+        if self == Symmetry::Both {
+            positions.push((mirrored_y, mirrored_x));
+        }
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+}
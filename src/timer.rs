@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+///something the editor does every `interval` while it waits for input
+pub struct Timer {
+    pub interval: Duration,
+    next: Instant,
+}
+
+impl Timer {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Instant::now() + interval,
+        }
+    }
+
+    ///true once the interval passed, the next one starts at `now`
+    pub fn is_due(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+        self.restart(now);
+        true
+    }
+
+    ///waits a whole interval again, from `now`
+    pub fn restart(&mut self, now: Instant) {
+        self.next = now + self.interval;
+    }
+}
+
+///milliseconds `getch` can wait for input before one of the timers is due,
+///-1 (block until there is input) without timers
+pub fn wait_time<'a>(timers: impl IntoIterator<Item = &'a Timer>, now: Instant) -> i32 {
+    timers
+        .into_iter()
+        .map(|timer| timer.next.saturating_duration_since(now))
+        .min()
+        //rounded up, a timer is never woken up before it is due
+        .map_or(-1, |wait| wait.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waits_for_the_next_timer() {
+        let now = Instant::now();
+        assert_eq!(wait_time([], now), -1);
+        let mut slow = Timer::new(Duration::from_secs(30));
+        let mut fast = Timer::new(Duration::from_millis(500));
+        slow.restart(now);
+        fast.restart(now);
+        assert_eq!(wait_time([&slow, &fast], now), 500);
+        let later = now + Duration::from_millis(600);
+        assert_eq!(wait_time([&slow, &fast], later), 0);
+        assert!(fast.is_due(later));
+        assert!(!fast.is_due(later));
+        assert!(!slow.is_due(later));
+        assert_eq!(wait_time([&slow, &fast], later), 500);
+    }
+}